@@ -0,0 +1,194 @@
+use hyper::Client;
+use hyper::header::{Headers, Authorization, Basic};
+use hyper::method::Method;
+use hyper::net::HttpsConnector;
+use hyper::status::StatusClass;
+use hyper::Url;
+use regex::Regex;
+use chrono::{DateTime, Local};
+use std::io::Read;
+use log::info;
+use crate::ical::format_utc;
+use crate::scheduler_error::SchedulerError;
+
+/// A single calendar resource (one .ics file) in a CalDAV collection.
+#[derive(Debug, Clone)]
+pub struct CalendarObject {
+    pub href: String,
+    pub etag: Option<String>,
+    pub data: String,
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct CalDavCredentials {
+    pub username: String,
+    pub password: String
+}
+
+pub struct CalDavClient {
+    collection: Url,
+    credentials: CalDavCredentials,
+    client: Client,
+}
+
+impl CalDavClient {
+    pub fn new(collection_url: &str, credentials: CalDavCredentials) -> anyhow::Result<CalDavClient> {
+        // Collections are directories - make sure relative hrefs resolve inside them
+        let url = if collection_url.ends_with('/') { collection_url.to_string() } else { format!("{}/", collection_url) };
+        Ok(CalDavClient {
+            collection: Url::parse(&url)?,
+            credentials,
+            client: Client::with_connector(HttpsConnector::new(hyper_rustls::TlsClient::new())),
+        })
+    }
+
+    /// Fetch every VEVENT overlapping the given window, along with every VTODO in the collection.
+    pub fn calendar_objects(&self, start: DateTime<Local>, end: DateTime<Local>) -> anyhow::Result<Vec<CalendarObject>> {
+        let mut objects = self.report(&calendar_query("VEVENT", Some((start, end))))?;
+        objects.extend(self.report(&calendar_query("VTODO", None))?);
+        Ok(objects)
+    }
+
+    /// Create (etag = None) or overwrite (etag = Some) a calendar resource
+    pub fn put(&self, href: &str, data: &str, etag: Option<&String>) -> anyhow::Result<()> {
+        let mut headers = self.headers();
+        headers.set_raw("Content-Type", vec![b"text/calendar; charset=utf-8".to_vec()]);
+        match etag {
+            Some(etag) => headers.set_raw("If-Match", vec![etag.as_bytes().to_vec()]),
+            None => headers.set_raw("If-None-Match", vec![b"*".to_vec()]),
+        }
+
+        let url = self.collection.join(href)?;
+        info!("CalDAV PUT {}", url);
+        self.send(Method::Put, url, headers, Some(data))?;
+        Ok(())
+    }
+
+    pub fn delete(&self, href: &str, etag: Option<&String>) -> anyhow::Result<()> {
+        let mut headers = self.headers();
+        if let Some(etag) = etag {
+            headers.set_raw("If-Match", vec![etag.as_bytes().to_vec()]);
+        }
+
+        let url = self.collection.join(href)?;
+        info!("CalDAV DELETE {}", url);
+        self.send(Method::Delete, url, headers, None)?;
+        Ok(())
+    }
+
+    fn report(&self, query: &str) -> anyhow::Result<Vec<CalendarObject>> {
+        let mut headers = self.headers();
+        headers.set_raw("Depth", vec![b"1".to_vec()]);
+        headers.set_raw("Content-Type", vec![b"application/xml; charset=utf-8".to_vec()]);
+
+        let body = self.send(Method::Extension("REPORT".to_string()), self.collection.clone(), headers, Some(query))?;
+        Ok(parse_multistatus(&body))
+    }
+
+    fn headers(&self) -> Headers {
+        let mut headers = Headers::new();
+        headers.set(Authorization(Basic {
+            username: self.credentials.username.clone(),
+            password: Some(self.credentials.password.clone())
+        }));
+        headers
+    }
+
+    fn send(&self, method: Method, url: Url, headers: Headers, body: Option<&str>) -> anyhow::Result<String> {
+        let mut request = self.client.request(method, url.clone()).headers(headers);
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+
+        let mut response = request.send().map_err(SchedulerError::from)?;
+        let mut contents = String::new();
+        response.read_to_string(&mut contents)?;
+
+        if response.status.class() != StatusClass::Success {
+            return Err(SchedulerError::from_status(response.status.to_u16(), format!("CalDAV request to {} failed: {}", url, response.status)).into());
+        }
+
+        Ok(contents)
+    }
+}
+
+fn calendar_query(component: &str, time_range: Option<(DateTime<Local>, DateTime<Local>)>) -> String {
+    let filter = match time_range {
+        Some((start, end)) => format!("<C:time-range start=\"{}\" end=\"{}\"/>", format_utc(start), format_utc(end)),
+        None => String::new()
+    };
+
+    format!(r#"<?xml version="1.0" encoding="utf-8" ?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop>
+    <D:getetag/>
+    <C:calendar-data/>
+  </D:prop>
+  <C:filter>
+    <C:comp-filter name="VCALENDAR">
+      <C:comp-filter name="{}">{}</C:comp-filter>
+    </C:comp-filter>
+  </C:filter>
+</C:calendar-query>"#, component, filter)
+}
+
+fn parse_multistatus(body: &str) -> Vec<CalendarObject> {
+    lazy_static! {
+        // Servers pick their own namespace prefixes (or none at all), so ignore them.
+        static ref RESPONSE_REGEX: Regex = Regex::new(r"(?s)<(?:\w+:)?response\b[^>]*>(.*?)</(?:\w+:)?response>").unwrap();
+        static ref HREF_REGEX: Regex = Regex::new(r"(?s)<(?:\w+:)?href\b[^>]*>(.*?)</(?:\w+:)?href>").unwrap();
+        static ref ETAG_REGEX: Regex = Regex::new(r"(?s)<(?:\w+:)?getetag\b[^>]*>(.*?)</(?:\w+:)?getetag>").unwrap();
+        static ref DATA_REGEX: Regex = Regex::new(r"(?s)<(?:\w+:)?calendar-data\b[^>]*>(.*?)</(?:\w+:)?calendar-data>").unwrap();
+    }
+
+    RESPONSE_REGEX.captures_iter(body).
+        filter_map(|response| {
+            let response = &response[1];
+            let href = HREF_REGEX.captures(response)?;
+            let data = DATA_REGEX.captures(response)?;
+            Some(CalendarObject {
+                href: unescape_xml(href[1].trim()),
+                etag: ETAG_REGEX.captures(response).map(|e| unescape_xml(e[1].trim())),
+                data: unescape_xml(&data[1]),
+            })
+        }).
+        collect()
+}
+
+fn unescape_xml(text: &str) -> String {
+    let text = text.trim_start_matches("<![CDATA[").trim_end_matches("]]>");
+    text.replace("&lt;", "<").
+        replace("&gt;", ">").
+        replace("&quot;", "\"").
+        replace("&apos;", "'").
+        replace("&#13;", "\r").
+        replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_multistatus_responses_regardless_of_prefix() {
+        let body = r#"<?xml version="1.0"?>
+<multistatus xmlns="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <response>
+    <href>/joe/cal/one.ics</href>
+    <propstat><prop><getetag>&quot;123&quot;</getetag><C:calendar-data>BEGIN:VCALENDAR&#13;
+END:VCALENDAR</C:calendar-data></prop></propstat>
+  </response>
+  <d:response>
+    <d:href>/joe/cal/two.ics</d:href>
+    <d:propstat><d:prop><d:getetag>"456"</d:getetag><cal:calendar-data>BEGIN:VCALENDAR</cal:calendar-data></d:prop></d:propstat>
+  </d:response>
+</multistatus>"#;
+
+        let objects = parse_multistatus(body);
+        assert_eq!(2, objects.len());
+        assert_eq!("/joe/cal/one.ics", objects[0].href);
+        assert_eq!(Some("\"123\"".to_string()), objects[0].etag);
+        assert_eq!("BEGIN:VCALENDAR\r\nEND:VCALENDAR", objects[0].data);
+        assert_eq!("/joe/cal/two.ics", objects[1].href);
+    }
+}
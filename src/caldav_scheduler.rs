@@ -0,0 +1,319 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities, is_all_day, rescheduled};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::caldav_client::{CalDavClient, CalDavCredentials, CalendarObject};
+use crate::ical::{self, Component, Property};
+use crate::commands::UICommand;
+use chrono::{DateTime, Local, Duration};
+use std::error::Error;
+use std::fs::File;
+use std::ops::Add;
+use std::sync::mpsc::Sender;
+use log::info;
+
+pub struct CalDavScheduler {
+    name: String,
+    client: CalDavClient,
+    objects: Vec<(CalendarObject, Component)>,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_caldav_scheduler(name: String, url: String, ui_tx: Sender<UICommand>) -> Result<CalDavScheduler, Box<dyn Error>> {
+    let file = File::open(format!("config/{}.json", name))?;
+    let credentials: CalDavCredentials = serde_json::from_reader(file).expect("Badly formatted CalDAV credentials file!");
+    let client = CalDavClient::new(&url, credentials)?;
+    Ok(CalDavScheduler::new(name, client, ui_tx))
+}
+
+impl CalDavScheduler {
+    pub fn new(name: String, client: CalDavClient, ui_tx: Sender<UICommand>) -> Self {
+        CalDavScheduler { name, client, ui_tx, objects: Vec::new(), cache: Vec::new() }
+    }
+
+    fn find_by_uid(&mut self, id: &str) -> Option<&mut (CalendarObject, Component)> {
+        // Occurrences of repeating events are found through the series they belong to
+        let series_uid = split_occurrence(id).0;
+        self.objects.iter_mut().find(|(_, cal)| {
+            cal.components.iter().any(|c| is_item(c) && c.value("UID").map_or(false, |u| u == id || u == series_uid))
+        })
+    }
+}
+
+impl Scheduler for CalDavScheduler {
+    fn id(&self) -> String {
+        format!("caldav:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        // Todos get completed, events deleted
+        Capabilities { add: true, reschedule: true, complete: true, delete: true, times: true, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let window_start = Local::now().add(Duration::minutes(-10));
+        let window_end = Local::now().add(Duration::days(2));
+
+        let mut objects = vec![];
+        for object in self.client.calendar_objects(window_start, window_end)? {
+            match ical::parse(&object.data) {
+                Ok(mut cals) if !cals.is_empty() => objects.push((object, cals.remove(0))),
+                Ok(_) => info!("No calendar found in {}", object.href),
+                Err(e) => info!("Could not parse {}: {}", object.href, e)
+            }
+        }
+
+        let scheduler_id = self.id();
+        let mut items = vec![];
+        for (_, cal) in &objects {
+            let events: Vec<&Component> = cal.components.iter().filter(|c| c.name == "VEVENT").collect();
+            items.extend(ical::expand(&events, window_start, window_end).into_iter().
+                filter_map(|(event, start_time)| occurrence_to_scheduled_item(&scheduler_id, event, start_time)));
+            items.extend(cal.components.iter().
+                filter(|c| c.name == "VTODO").
+                filter_map(|c| component_to_scheduled_item(&scheduler_id, c)));
+        }
+        self.cache = items;
+        self.objects = objects;
+
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        info!("Adding '{}' to CalDAV calendar {}", description, self.name);
+        let uid = format!("{}-todor", Local::now().timestamp_nanos());
+
+        let mut todo = Component::new("VTODO");
+        todo.set(Property::new("UID", uid.clone()));
+        todo.set(ical::datetime_property("DTSTAMP", Local::now()));
+        todo.set(Property::new("SUMMARY", ical::escape_text(description)));
+        let due_date = due_date.unwrap_or(Local::now());
+        todo.set(if is_all_day(due_date) { ical::date_property("DUE", due_date) } else { ical::datetime_property("DUE", due_date) });
+        todo.set(Property::new("STATUS", "NEEDS-ACTION".to_string()));
+
+        let mut cal = Component::new("VCALENDAR");
+        cal.set(Property::new("VERSION", "2.0".to_string()));
+        cal.set(Property::new("PRODID", "-//todor//EN".to_string()));
+        cal.components.push(todo);
+
+        self.client.put(&format!("{}.ics", uid), &cal.to_ical(), None)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let (object, mut cal) = match self.find_by_uid(id) {
+            None => return Err(SchedulerError::NotFound(format!("No CalDAV item with UID {}", id))),
+            Some((object, cal)) => (object.clone(), cal.clone())
+        };
+
+        // Moving the whole series because one occurrence was rescheduled would be a nasty surprise
+        if due_date.is_some() && cal.components.iter().any(is_repeating) {
+            return Err(SchedulerError::Other("Can't reschedule one occurrence of a repeating event - move it in your calendar instead".to_string()));
+        }
+
+        for component in cal.components.iter_mut().filter(|c| is_item(c)) {
+            component.set(Property::new("SUMMARY", ical::escape_text(description)));
+            component.set(ical::datetime_property("DTSTAMP", Local::now()));
+            if let Some(due_date) = due_date {
+                reschedule_component(component, due_date);
+            }
+        }
+
+        self.client.put(&object.href, &cal.to_ical(), object.etag.as_ref())?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Looking for {} in CalDAV calendar {}", id, self.name);
+        let (object, mut cal) = match self.find_by_uid(id) {
+            None => return Err(SchedulerError::NotFound(format!("No CalDAV item with UID {}", id))),
+            Some((object, cal)) => (object.clone(), cal.clone())
+        };
+
+        if cal.components.iter().any(|c| c.name == "VTODO") {
+            for todo in cal.components.iter_mut().filter(|c| c.name == "VTODO") {
+                todo.set(Property::new("STATUS", "COMPLETED".to_string()));
+                todo.set(Property::new("PERCENT-COMPLETE", "100".to_string()));
+                todo.set(ical::datetime_property("COMPLETED", Local::now()));
+                todo.set(ical::datetime_property("DTSTAMP", Local::now()));
+            }
+            self.client.put(&object.href, &cal.to_ical(), object.etag.as_ref())?;
+        } else if let Some(occurrence) = split_occurrence(id).1.filter(|_| cal.components.iter().any(|c| c.get("RRULE").is_some())) {
+            exclude_occurrence(&mut cal, occurrence);
+            self.client.put(&object.href, &cal.to_ical(), object.etag.as_ref())?;
+        } else {
+            self.client.delete(&object.href, object.etag.as_ref())?;
+        }
+
+        Ok(true)
+    }
+}
+
+fn is_item(c: &Component) -> bool {
+    c.name == "VEVENT" || c.name == "VTODO"
+}
+
+fn is_repeating(c: &Component) -> bool {
+    c.get("RRULE").is_some() || c.get("RECURRENCE-ID").is_some()
+}
+
+/// Split an item id into the UID and, for an occurrence of a repeating event, when it starts
+fn split_occurrence(id: &str) -> (&str, Option<&str>) {
+    match id.rsplit_once('/') {
+        Some((uid, start)) => (uid, Some(start)),
+        None => (id, None)
+    }
+}
+
+/// Drop one occurrence from a repeating event, along with any modified copy of it
+fn exclude_occurrence(cal: &mut Component, occurrence: &str) {
+    let time = match ical::property_to_datetime(&Property::new("EXDATE", occurrence.to_string())) {
+        Some(t) => t,
+        None => return
+    };
+
+    cal.components.retain(|c| c.get("RECURRENCE-ID").and_then(ical::property_to_datetime) != Some(time));
+    for event in cal.components.iter_mut().filter(|c| c.get("RRULE").is_some()) {
+        let all_day = event.get("DTSTART").map_or(false, ical::is_date_only);
+        // EXDATE may be repeated, so add another rather than replacing the ones already there
+        event.properties.push(if all_day { ical::date_property("EXDATE", time) } else { ical::datetime_property("EXDATE", time) });
+    }
+}
+
+fn reschedule_component(component: &mut Component, due_date: DateTime<Local>) {
+    let start_name = if component.name == "VTODO" && (component.get("DUE").is_some() || component.get("DTSTART").is_none()) { "DUE" } else { "DTSTART" };
+    let old_start = component.get(start_name).cloned();
+    let old_start_time = old_start.as_ref().and_then(ical::property_to_datetime);
+    let all_day = old_start.as_ref().map_or(false, ical::is_date_only);
+
+    // A new day on its own keeps the time of day it had
+    let new_start_time = match old_start_time {
+        Some(old_start_time) if !all_day => rescheduled(old_start_time, due_date),
+        _ => due_date
+    };
+    component.set(if all_day { ical::date_property(start_name, new_start_time) } else { ical::datetime_property(start_name, new_start_time) });
+
+    // Events keep their length when they move
+    let old_end = component.get("DTEND").cloned();
+    if let (Some(old_start_time), Some(old_end)) = (old_start_time, old_end) {
+        if let Some(old_end_time) = ical::property_to_datetime(&old_end) {
+            let new_end = new_start_time + (old_end_time - old_start_time);
+            component.set(if all_day { ical::date_property("DTEND", new_end) } else { ical::datetime_property("DTEND", new_end) });
+        }
+    }
+}
+
+/// One occurrence of an event. Repeating events get the start of the occurrence in their id, so
+/// each one can be told apart.
+fn occurrence_to_scheduled_item(scheduler_id: &String, event: &Component, start_time: DateTime<Local>) -> Option<ScheduledItem> {
+    let mut item = component_to_scheduled_item(scheduler_id, event)?;
+    if is_repeating(event) {
+        item.id = ItemId::new(scheduler_id, &format!("{}/{}", item.id.native, ical::format_utc(start_time)));
+    }
+    item.end_time = item.end_time.map(|end| start_time + (end - item.start_time));
+    item.start_time = start_time;
+    Some(item)
+}
+
+fn component_to_scheduled_item(scheduler_id: &String, c: &Component) -> Option<ScheduledItem> {
+    let mut item = component_to_bare_item(scheduler_id, c)?;
+    // PRIORITY runs from 1 (highest) to 9, with 0 meaning "none"
+    item.priority = c.value("PRIORITY").and_then(|p| p.parse::<u8>().ok()).filter(|p| *p > 0);
+    item.tags = c.get_all("CATEGORIES").iter().
+        flat_map(|p| p.value.split(',').map(|t| ical::unescape_text(t.trim())).collect::<Vec<_>>()).
+        collect();
+    item.notes = c.text("DESCRIPTION");
+    item.link = c.value("URL").cloned();
+    Some(item)
+}
+
+fn component_to_bare_item(scheduler_id: &String, c: &Component) -> Option<ScheduledItem> {
+    let uid = c.value("UID")?;
+    let description = c.text("SUMMARY").unwrap_or("no desc".to_string());
+    let place = c.text("LOCATION");
+
+    match c.name.as_str() {
+        "VEVENT" => {
+            let start = c.get("DTSTART")?;
+            let start_time = ical::property_to_datetime(start)?;
+            let end_time = c.get("DTEND").and_then(ical::property_to_datetime);
+            let mut item = ScheduledItem::new(ItemId::new(scheduler_id, uid), ScheduleItemType::Calendar,
+                                              description, start_time, end_time, place);
+            item.all_day = ical::is_date_only(start);
+            Some(item)
+        }
+        "VTODO" => {
+            let done = c.get("COMPLETED").is_some() ||
+                c.value("STATUS").map_or(false, |s| s == "COMPLETED" || s == "CANCELLED");
+            if done { return None; }
+
+            let due_property = c.get("DUE").or(c.get("DTSTART"));
+            let due = due_property.
+                and_then(ical::property_to_datetime).
+                unwrap_or(Local::today().and_hms(23, 59, 59));
+            let mut item = ScheduledItem::new(ItemId::new(scheduler_id, uid), ScheduleItemType::Todo,
+                                              description, due, None, place);
+            item.all_day = due_property.map_or(true, ical::is_date_only);
+            item.undated = due_property.is_none();
+            Some(item)
+        }
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn calendar(event: &str) -> Component {
+        let text = format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", event);
+        ical::parse(&text).unwrap().remove(0)
+    }
+
+    #[test]
+    fn rescheduling_to_a_day_keeps_the_time_and_length() {
+        let start = Local.ymd(2026, 10, 1).and_hms(9, 0, 0);
+        let mut cal = calendar(&format!("BEGIN:VEVENT\r\nUID:standup\r\nDTSTART:{}\r\nDTEND:{}\r\nEND:VEVENT\r\n",
+                                        ical::format_utc(start), ical::format_utc(start + Duration::minutes(30))));
+
+        reschedule_component(&mut cal.components[0], Local.ymd(2026, 10, 5).and_hms(23, 59, 59));
+
+        let event = &cal.components[0];
+        assert_eq!(Some(Local.ymd(2026, 10, 5).and_hms(9, 0, 0)), event.get("DTSTART").and_then(ical::property_to_datetime));
+        assert_eq!(Some(Local.ymd(2026, 10, 5).and_hms(9, 30, 0)), event.get("DTEND").and_then(ical::property_to_datetime));
+    }
+
+    #[test]
+    fn repeating_events_show_every_occurrence() {
+        let start = Local.ymd(2026, 10, 1).and_hms(9, 0, 0);
+        let mut cal = calendar(&format!("BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup\r\nDTSTART:{}\r\nDTEND:{}\r\nRRULE:FREQ=DAILY\r\nEND:VEVENT\r\n",
+                                        ical::format_utc(start), ical::format_utc(start + Duration::minutes(15))));
+        let scheduler_id = "caldav:work".to_string();
+        let window = (Local.ymd(2026, 10, 3).and_hms(0, 0, 0), Local.ymd(2026, 10, 4).and_hms(23, 0, 0));
+
+        let events: Vec<&Component> = cal.components.iter().collect();
+        let items: Vec<ScheduledItem> = ical::expand(&events, window.0, window.1).into_iter().
+            filter_map(|(event, start_time)| occurrence_to_scheduled_item(&scheduler_id, event, start_time)).
+            collect();
+        assert_eq!(2, items.len());
+        assert_eq!(Local.ymd(2026, 10, 4).and_hms(9, 0, 0), items[1].start_time);
+        assert_eq!(Some(Local.ymd(2026, 10, 4).and_hms(9, 15, 0)), items[1].end_time);
+        assert_ne!(items[0].id, items[1].id);
+
+        // Removing one of them leaves the rest of the series alone
+        exclude_occurrence(&mut cal, split_occurrence(&items[0].id.native).1.unwrap());
+        let events: Vec<&Component> = cal.components.iter().collect();
+        let left = ical::expand(&events, window.0, window.1);
+        assert_eq!(vec![Local.ymd(2026, 10, 4).and_hms(9, 0, 0)], left.iter().map(|(_, t)| *t).collect::<Vec<_>>());
+    }
+}
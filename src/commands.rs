@@ -1,10 +1,10 @@
-use crate::scheduled_item::ScheduledItem;
+use crate::scheduled_item::{ScheduledItem, ItemComment, ItemId, Capabilities};
 use crate::display::PromptMessage;
 
 #[derive(Clone, Debug)]
 pub enum UICommand {
     Schedules(Vec<ScheduledItem>),
-    Schedulers(Vec<String>),
+    Schedulers(Vec<(SchedulerAccountId, Capabilities)>),
 
     Toast(PromptMessage),
     SetPrompt(String),
@@ -20,10 +20,15 @@ pub enum UICommand {
     ClearSelection,
     SelectPrev,
     SelectNext,
+    ExpandSelection,
+    CollapseSelection,
 
     AddGetScheduler(String),
     Add(SchedulerAccountId, String),
 
+    Comments(ScheduledItem, Vec<ItemComment>),
+    Disambiguate(String, Vec<ScheduledItem>), // Several items matched - pick one to run the command on
+
     Exit
 }
 
@@ -33,6 +38,10 @@ pub type SchedulerAccountId = String;
 pub enum ScheduleCommand {
     Refresh,
     Add(SchedulerAccountId, String),
-    CloseTodo(SchedulerAccountId, String),
-    Reschedule(SchedulerAccountId, ScheduledItem, String),
+    // Commands about an existing item find their scheduler through the item's id
+    CloseTodo(ItemId),
+    CloseByDescription(String), // typed at the prompt, nothing selected
+    Reschedule(ItemId, String, String), // the item's description, and when to move it to
+    Comments(ScheduledItem),
+    AddComment(ScheduledItem, String),
 }
\ No newline at end of file
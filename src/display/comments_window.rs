@@ -0,0 +1,79 @@
+use crate::display::{Window, CommentsWindow};
+use crate::commands::UICommand;
+use std::io::Write;
+use crate::scheduled_item::ScheduledItem;
+use std::sync::mpsc::Sender;
+
+impl CommentsWindow {
+    pub fn new(ui_tx: Sender<UICommand>) -> Self {
+        CommentsWindow {
+            ui_tx,
+            item: None,
+            comments: None
+        }
+    }
+}
+
+impl Window for CommentsWindow {
+    fn id(&self) -> String {
+        "comments".to_string()
+    }
+
+    fn active(&self) -> bool {
+        true
+    }
+
+    fn enable(&mut self) { }
+
+    fn disable(&mut self) { }
+
+    fn handle(&mut self, ui_cmd: &UICommand) -> bool {
+        match ui_cmd {
+            UICommand::Comments(item, comments) => {
+                self.item = Some(item.clone());
+                self.comments = Some(comments.clone());
+                true
+            }
+            UICommand::SelectNext | UICommand::SelectPrev => { true } // Nothing to select in here
+            UICommand::ClearSelection => { self.ui_tx.send(UICommand::TransitionPop); true }
+            UICommand::SubmitCommand(text) => {
+                // Anything typed here becomes a new comment. An empty line closes the window.
+                match (&self.item, text.trim().is_empty()) {
+                    (Some(item), false) => { self.ui_tx.send(UICommand::ExecuteWithItem(format!("comment {}", text.trim()), item.clone())); }
+                    _ => { self.ui_tx.send(UICommand::TransitionPop); }
+                }
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn render(&self, target: &mut dyn Write) {
+        let mut output = vec![];
+
+        let title = self.item.as_ref().map_or("Comments".to_string(), |i| i.description.clone());
+        write!(output, "{}{}{}\r\n-------------\r\n",
+               termion::clear::All,
+               termion::cursor::Goto(1, 1),
+               title);
+
+        match &self.comments {
+            None => { write!(output, "Loading...\r\n"); }
+            Some(comments) if comments.is_empty() => { write!(output, "No comments yet\r\n"); }
+            Some(comments) => {
+                for c in comments {
+                    let posted = c.posted_at.map_or("".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string());
+                    write!(output, "{} {}\r\n", posted, c.content.replace("\n", "\r\n    "));
+                }
+            }
+        }
+
+        write!(output, "\r\nType a comment and hit <enter> to add it, or just <enter> to go back\r\n");
+
+        write!(target, "{}", String::from_utf8(output).unwrap());
+    }
+
+    fn selected_item(&self) -> Option<&ScheduledItem> {
+        self.item.as_ref()
+    }
+}
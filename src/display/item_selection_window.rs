@@ -0,0 +1,80 @@
+use crate::display::{Window, ItemSelectionWindow};
+use crate::commands::UICommand;
+use std::io::Write;
+use crate::scheduled_item::ScheduledItem;
+use std::cmp::{max, min};
+use std::sync::mpsc::Sender;
+
+impl ItemSelectionWindow {
+    pub fn new(ui_tx: Sender<UICommand>) -> Self {
+        ItemSelectionWindow {
+            ui_tx,
+            command: None,
+            items: vec![],
+            selected_item_idx: -1
+        }
+    }
+}
+
+impl Window for ItemSelectionWindow {
+    fn id(&self) -> String {
+        "item_selection".to_string()
+    }
+
+    fn active(&self) -> bool {
+        true
+    }
+
+    fn enable(&mut self) { }
+
+    fn disable(&mut self) { }
+
+    fn handle(&mut self, ui_cmd: &UICommand) -> bool {
+        match ui_cmd {
+            UICommand::Disambiguate(command, items) => {
+                self.command = Some(command.clone());
+                self.items = items.clone();
+                true
+            }
+            UICommand::ClearSelection => { self.ui_tx.send(UICommand::TransitionPop); true }
+            UICommand::SelectPrev => { self.selected_item_idx = max(-1, self.selected_item_idx - 1); true }
+            UICommand::SelectNext => { self.selected_item_idx = min(self.items.len() as i32 - 1, self.selected_item_idx + 1); true }
+            UICommand::UpdateUserInput(_) => { true } // Block typing to the prompt until we have a selection
+            UICommand::SubmitCommand(_) => {
+                match (&self.command, self.selected_item()) {
+                    (Some(command), Some(item)) => { self.ui_tx.send(UICommand::ExecuteWithItem(command.clone(), item.clone())); }
+                    _ => {}
+                }
+                self.ui_tx.send(UICommand::TransitionPop);
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn render(&self, target: &mut dyn Write) {
+        let mut output = vec![];
+
+        write!(output, "{}{}Which one?\r\n-------------\r\n",
+            termion::clear::All,
+            termion::cursor::Goto(1, 1));
+
+        for (i, item) in self.items.iter().enumerate() {
+            let marker = if self.selected_item_idx == i as i32 { ">" } else { " " };
+            write!(output, "{} {} - {} ({})\r\n",
+                   marker,
+                   if item.undated { "No due date".to_string() } else { item.start_time.format("%a %b %e %H:%M").to_string() },
+                   item.description,
+                   item.scheduler());
+        }
+
+        write!(target, "{}", String::from_utf8(output).unwrap());
+    }
+
+    fn selected_item(&self) -> Option<&ScheduledItem> {
+        match self.selected_item_idx {
+            -1 => None,
+            idx => self.items.get(idx as usize)
+        }
+    }
+}
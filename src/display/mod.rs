@@ -1,16 +1,19 @@
 use crate::commands::UICommand;
 use chrono::{DateTime, Local};
-use crate::scheduled_item::ScheduledItem;
+use crate::scheduled_item::{ScheduledItem, ItemComment, ItemId, Capabilities};
 use std::sync::mpsc::Sender;
+use std::collections::HashSet;
 
 mod schedule_window;
 mod prompt_window;
 mod window_stack;
 mod schedule_selection_window;
+mod comments_window;
+mod item_selection_window;
 
 pub struct WindowStack {
     windows: Vec<Box<dyn Window>>,
-    scheduler_ids: Vec<String>,
+    schedulers: Vec<(String, Capabilities)>,
     pub ui_tx: Sender<UICommand>,
 }
 
@@ -40,6 +43,9 @@ pub struct PromptWindow {
 pub struct ScheduleWindow {
     active: bool,
     schedules: Vec<ScheduledItem>,
+    visible: Vec<(ScheduledItem, usize)>, // In display order, with subtask depth
+    collapsed: HashSet<ItemId>,
+    pending_close: Option<ItemId>,
     selected_item_idx: i32,
     pub ui_tx: Sender<UICommand>,
 }
@@ -52,6 +58,21 @@ pub struct ScheduleSelectionWindow {
     pub task: Option<String>,
 }
 
+#[derive(Clone, Debug)]
+pub struct ItemSelectionWindow {
+    ui_tx: Sender<UICommand>,
+    command: Option<String>, // run against whichever item gets picked
+    items: Vec<ScheduledItem>,
+    selected_item_idx: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct CommentsWindow {
+    ui_tx: Sender<UICommand>,
+    item: Option<ScheduledItem>,
+    comments: Option<Vec<ItemComment>>, // None until they've loaded
+}
+
 pub trait Window {
     fn id(&self) -> String;
     fn active(&self) -> bool;
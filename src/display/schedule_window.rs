@@ -1,7 +1,10 @@
-use crate::display::{Window, ScheduleWindow};
-use crate::scheduled_item::ScheduledItem;
+use crate::display::{Window, ScheduleWindow, PromptMessage, PromptMessageType};
+use crate::scheduled_item::{ScheduledItem, ItemId};
+use chrono::{Date, Local};
 use crate::commands::UICommand;
 use std::cmp::{min, max};
+use std::collections::HashSet;
+use std::time::Duration;
 use crate::MAX_WIDTH;
 use itertools::*;
 use crate::schedule_formatter::format_item;
@@ -9,56 +12,134 @@ use crate::schedule_colorer::color_item;
 use std::io::Write;
 use std::sync::mpsc::Sender;
 
+/// Where an item is listed
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Overdue,
+    Day(Date<Local>, bool), // and whether it's the day's all-day items
+    Undated
+}
+
 impl ScheduleWindow {
     pub fn new(ui_tx: Sender<UICommand>) -> Self {
         ScheduleWindow {
             ui_tx,
             active: true,
             schedules: vec![],
+            visible: vec![],
+            collapsed: HashSet::new(),
+            pending_close: None,
             selected_item_idx: -1
         }
     }
 
-    fn render_schedule(&self, items: &Vec<ScheduledItem>, selected_item_idx: i32, stdout: &mut dyn Write) -> anyhow::Result<()> {
+    fn render_schedule(&self, selected_item_idx: i32, stdout: &mut dyn Write) -> anyhow::Result<()> {
         let (_, rows) = termion::terminal_size().unwrap();
 
         // Clear the screen and go to the top line before we start
         stdout.write(b"\x1B[2J\x1B[1;1H")?;
 
-        let mut items = items.clone();
-        items.sort_by_key(|f| f.start_time);
-
         // max_width is determined by the widest description or MAX_WIDTH, whichever is smaller.
-        let max_width = min(MAX_WIDTH, items.iter().map(|i| i.description.len()).max().unwrap_or(MAX_WIDTH));
-
-        let grouped_by_date = items.into_iter().group_by(|item| item.start_time.date());
-        let mut item_count = 0;
+        let max_width = min(MAX_WIDTH, self.visible.iter().map(|(i, depth)| i.description.len() + depth * 2).max().unwrap_or(MAX_WIDTH));
+
+        // Subtasks are listed under their parent, even when they're due on another day.
+        // Overdue work gets a section of its own, whatever day it was due, and so does anything never due.
+        let today = Local::today();
+        let section = |item: &ScheduledItem| {
+            if item.days_overdue(today).is_some() { Section::Overdue }
+            else if item.undated { Section::Undated }
+            else { Section::Day(item.start_time.date(), item.all_day) }
+        };
+        let mut root_section = None;
+        let grouped_by_date = self.visible.iter().enumerate().group_by(|(_, (item, depth))| {
+            if *depth == 0 { root_section = Some(section(item)); }
+            root_section.unwrap_or(section(item))
+        });
 
         let mut output = Vec::new();
-        for (date, items_for_date) in &grouped_by_date
+        let mut last_heading = None;
+        for (section, items_for_date) in &grouped_by_date
         {
-            let ds: String = date.to_string();
-            // Print the date
-            write!(output, "{}\n\r", ds.get(0..ds.len() - 6).unwrap())?;
-            let item_vec: Vec<ScheduledItem> = items_for_date.collect::<Vec<ScheduledItem>>();
-            write!(output, "--------{}---------\r\n", item_vec.len())?; // divider
+            // Print the date - once, even when the day has both all-day and timed items
+            let heading = match section {
+                Section::Overdue => "Overdue".to_string(),
+                Section::Undated => "No due date".to_string(),
+                Section::Day(date, _) => { let ds = date.to_string(); ds.get(0..ds.len() - 6).unwrap().to_string() }
+            };
+            if last_heading.as_ref() != Some(&heading) {
+                if last_heading.is_some() { write!(output, "\n\r")?; }
+                write!(output, "{}\n\r", heading)?;
+                last_heading = Some(heading);
+            }
+
+            let item_vec = items_for_date.collect::<Vec<_>>();
+            let kind = if let Section::Day(_, true) = section { " all day" } else { "" };
+            write!(output, "--------{}{}---------\r\n", item_vec.len(), kind)?; // divider
 
             // Print the date's schedule
-            for item in item_vec {
-                match format_item(&item, item_count == selected_item_idx, max_width) {
-                    Some(s) => { write!(output, "  {}\n\r", color_item(&item, &s))?; },
+            for (idx, (item, depth)) in item_vec {
+                let width = max_width.saturating_sub(depth * 2);
+                match format_item(&item, idx as i32 == selected_item_idx, width) {
+                    Some(s) => {
+                        let marker = if self.collapsed.contains(&item.id) && self.has_children(item) { "+" } else { " " };
+                        write!(output, " {}{}{}\n\r", marker, "  ".repeat(*depth), color_item(&item, &s))?;
+                    },
                     None => {}
                 }
-                item_count += 1;
             }
-
-            write!(output, "\n\r")?;
         }
 
         write!(stdout, "{}", String::from_utf8(output).unwrap().split("\n").take(rows as usize - 1).join("\n"))?;
 
         Ok(())
     }
+
+    fn has_children(&self, item: &ScheduledItem) -> bool {
+        self.schedules.iter().any(|i| i.parent_id.as_ref() == Some(&item.id))
+    }
+
+    fn open_children(&self, item: &ScheduledItem) -> Vec<ScheduledItem> {
+        self.schedules.iter().filter(|i| i.parent_id.as_ref() == Some(&item.id)).cloned().collect()
+    }
+
+    /// Work out what's on screen and in what order: everything sorted by time, with
+    /// subtasks pulled up under their parents - unless the parent is collapsed.
+    fn arrange(&mut self) {
+        let mut items = self.schedules.clone();
+        // Overdue work first and undated work last, then all-day items head their day. Most urgent first when things are due at the same time
+        let today = Local::today();
+        items.sort_by_key(|f| (f.days_overdue(today).is_none(), f.undated, f.start_time.date(), !f.all_day, f.start_time, f.priority.unwrap_or(u8::MAX)));
+
+        let ids: HashSet<&ItemId> = items.iter().map(|i| &i.id).collect();
+        let roots = items.iter().filter(|i| i.parent_id.as_ref().map_or(true, |p| !ids.contains(p)));
+
+        let mut visible = vec![];
+        for root in roots {
+            self.add_with_children(root, 0, &items, &mut visible);
+        }
+
+        self.visible = visible;
+        self.selected_item_idx = min(self.visible.len() as i32 - 1, self.selected_item_idx);
+    }
+
+    fn add_with_children(&self, item: &ScheduledItem, depth: usize, items: &Vec<ScheduledItem>, visible: &mut Vec<(ScheduledItem, usize)>) {
+        visible.push((item.clone(), depth));
+        if self.collapsed.contains(&item.id) { return; }
+
+        for child in items.iter().filter(|i| i.parent_id.as_ref() == Some(&item.id)) {
+            self.add_with_children(child, depth + 1, items, visible);
+        }
+    }
+
+    fn set_collapsed(&mut self, collapsed: bool) {
+        let id = match self.selected_item() {
+            None => return,
+            Some(item) => item.id.clone()
+        };
+
+        if collapsed { self.collapsed.insert(id); } else { self.collapsed.remove(&id); }
+        self.arrange();
+    }
 }
 
 impl Window for ScheduleWindow {
@@ -80,17 +161,37 @@ impl Window for ScheduleWindow {
 
     fn handle(&mut self, data: &UICommand) -> bool {
         match data{
-            UICommand::Schedules(sched) => { self.schedules = sched.clone(); true }
-            UICommand::SelectNext => { self.selected_item_idx = min(self.schedules.len() as i32 - 1, self.selected_item_idx + 1); true }
-            UICommand::SelectPrev => { self.selected_item_idx = max(-1, self.selected_item_idx - 1); true }
-            UICommand::ClearSelection => { self.selected_item_idx = -1; true }
+            // Anything that changes what's selected (or what it has under it) calls off a close waiting to be confirmed
+            UICommand::Schedules(sched) => { self.schedules = sched.clone(); self.pending_close = None; self.arrange(); true }
+            UICommand::SelectNext => { self.selected_item_idx = min(self.visible.len() as i32 - 1, self.selected_item_idx + 1); self.pending_close = None; true }
+            UICommand::SelectPrev => { self.selected_item_idx = max(-1, self.selected_item_idx - 1); self.pending_close = None; true }
+            UICommand::ClearSelection => { self.selected_item_idx = -1; self.pending_close = None; true }
+            UICommand::ExpandSelection => { self.set_collapsed(false); true }
+            UICommand::CollapseSelection => { self.set_collapsed(true); true }
             UICommand::SubmitCommand(command) => {
-                match command.split_ascii_whitespace().next().unwrap_or("").to_lowercase().as_str() {
-                    "ack" | "close" | "reschedule" => {
+                let cmd = command.split_ascii_whitespace().next().unwrap_or("").to_lowercase();
+                match cmd.as_str() {
+                    "ack" | "close" | "reschedule" | "comment" | "comments" => {
                         // Check for a selected item
-                        match self.selected_item() {
+                        match self.selected_item().cloned() {
                             None => { self.ui_tx.send(UICommand::Execute(command.clone())); }
-                            Some(item) => { self.ui_tx.send(UICommand::ExecuteWithItem(command.clone(), item.clone())); }
+                            Some(item) => {
+                                // Closing a parent closes its subtasks too, so make sure that's what the user meant
+                                let closing = cmd == "ack" || cmd == "close";
+                                let children = if closing { self.open_children(&item) } else { vec![] };
+                                if !children.is_empty() && self.pending_close.as_ref() != Some(&item.id) {
+                                    let msg = format!("'{}' has {} open subtask(s) - {} again to close them all", item.description, children.len(), cmd);
+                                    self.ui_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Normal)));
+                                    self.pending_close = Some(item.id.clone());
+                                } else {
+                                    // Only some backends close subtasks along with their parent, so close them ourselves
+                                    self.pending_close = None;
+                                    for child in children {
+                                        self.ui_tx.send(UICommand::ExecuteWithItem(command.clone(), child));
+                                    }
+                                    self.ui_tx.send(UICommand::ExecuteWithItem(command.clone(), item));
+                                }
+                            }
                         }
                         true
                     }
@@ -103,13 +204,13 @@ impl Window for ScheduleWindow {
     }
 
     fn render(&self, target: &mut dyn Write) {
-        self.render_schedule(&self.schedules, self.selected_item_idx, target);
+        self.render_schedule(self.selected_item_idx, target);
     }
 
     fn selected_item(&self) -> Option<&ScheduledItem> {
         match self.selected_item_idx {
             -1 => None,
-            _  => self.schedules.get(self.selected_item_idx as usize)
+            _  => self.visible.get(self.selected_item_idx as usize).map(|(item, _)| item)
         }
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,4 @@
-use crate::display::{Window, PromptWindow, WindowStack, ScheduleSelectionWindow, ScheduleWindow};
+use crate::display::{Window, PromptWindow, WindowStack, ScheduleSelectionWindow, ScheduleWindow, CommentsWindow, ItemSelectionWindow};
 use std::io::Write;
 use crate::commands::UICommand;
 use log::info;
@@ -9,22 +9,30 @@ impl WindowStack {
         WindowStack {
             ui_tx: ui_tx.clone(),
             windows: vec![Box::new(PromptWindow::new(ui_tx))],
-            scheduler_ids: vec![]
+            schedulers: vec![]
         }
     }
 
     pub fn handle_ui_command(&mut self, cmd: UICommand, stdout: &mut dyn Write) {
         match cmd {
-            UICommand::Schedulers(schedulers) => { self.scheduler_ids = schedulers; }
+            UICommand::Schedulers(schedulers) => { self.schedulers = schedulers; }
 
             UICommand::TransitionPush(window_type) => {
                 let new_window: Option<Box<dyn Window>> = match window_type.to_lowercase().as_str() {
                     "schedule_selection" => {
-                        Some(Box::new(ScheduleSelectionWindow::new(self.scheduler_ids.clone(), self.ui_tx.clone())))
+                        // Only offer the schedulers that will take a new item
+                        let ids = self.schedulers.iter().filter(|(_, c)| c.add).map(|(id, _)| id.clone()).collect();
+                        Some(Box::new(ScheduleSelectionWindow::new(ids, self.ui_tx.clone())))
                     }
                     "schedule" => {
                         Some(Box::new(ScheduleWindow::new(self.ui_tx.clone())))
                     }
+                    "comments" => {
+                        Some(Box::new(CommentsWindow::new(self.ui_tx.clone())))
+                    }
+                    "item_selection" => {
+                        Some(Box::new(ItemSelectionWindow::new(self.ui_tx.clone())))
+                    }
                     _ => None
                 };
 
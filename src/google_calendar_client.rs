@@ -18,23 +18,30 @@ use std::fmt;
 use serde::de::StdError;
 use std::error::Error;
 
-pub fn create_gcal_client(auth_file: String) -> Result<CalendarHub<Client, Authenticator<DefaultAuthenticatorDelegate, JsonTokenStorage, Client>>, Box<dyn Error>> {
-    // Read in the auth file and configure ourselves
-    let secret = read_secret_file(auth_file.as_str())?;
-    let filename = format!("{}", Path::new(&auth_file.clone()).file_stem().unwrap().to_str().unwrap());
-
-    let auth = Authenticator::new(  &secret, DefaultAuthenticatorDelegate,
-                                    hyper::Client::with_connector(hyper::net::HttpsConnector::new(hyper_rustls::TlsClient::new())),
-                                    JsonTokenStorage {
-                                        program_name: filename,
-                                        db_dir: "config".to_string(),
-                                    }, Some(FlowType::InstalledRedirect(54324)));
+pub type GoogleAuthenticator = Authenticator<DefaultAuthenticatorDelegate, JsonTokenStorage, Client>;
 
+pub fn create_gcal_client(auth_file: String) -> Result<CalendarHub<Client, GoogleAuthenticator>, Box<dyn Error>> {
+    let auth = create_authenticator(&auth_file)?;
     let client = hyper::Client::with_connector(hyper::net::HttpsConnector::new(hyper_rustls::TlsClient::new()));
 
     return Ok(CalendarHub::new(client, auth))
 }
 
+/// Shared by every Google API we talk to. Tokens are stored per scope, so the same
+/// credentials file works for Calendar and Tasks.
+pub fn create_authenticator(auth_file: &str) -> Result<GoogleAuthenticator, Box<dyn Error>> {
+    // Read in the auth file and configure ourselves
+    let secret = read_secret_file(auth_file)?;
+    let filename = format!("{}", Path::new(auth_file).file_stem().unwrap().to_str().unwrap());
+
+    Ok(Authenticator::new(  &secret, DefaultAuthenticatorDelegate,
+                            hyper::Client::with_connector(hyper::net::HttpsConnector::new(hyper_rustls::TlsClient::new())),
+                            JsonTokenStorage {
+                                program_name: filename,
+                                db_dir: "config".to_string(),
+                            }, Some(FlowType::InstalledRedirect(54324))))
+}
+
 fn read_secret_file(auth_file: &str) -> std::result::Result<ApplicationSecret, Box<dyn Error>> {
     // Get an ApplicationSecret instance from a secret file. It contains the `client_id` and
     // `client_secret`, among other things.
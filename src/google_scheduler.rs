@@ -1,31 +1,36 @@
 use google_calendar3::{CalendarHub, Event, EventDateTime};
 use hyper::Client;
 use yup_oauth2::{Authenticator, DefaultAuthenticatorDelegate};
-use crate::scheduled_item::{ScheduledItem, Scheduler, ScheduleItemType};
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities, is_all_day, rescheduled};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
 use chrono::{DateTime, Local, Duration, TimeZone, Datelike, NaiveDate};
 use std::ops::Add;
 use std::error::Error;
 use crate::google_calendar_client::{JsonTokenStorage, create_gcal_client};
 use std::str::FromStr;
+use std::sync::mpsc::Sender;
 use log::info;
+use crate::commands::UICommand;
 
 pub struct GoogleScheduler {
     pub calendar_name: String,
     pub hub: CalendarHub<Client, Authenticator<DefaultAuthenticatorDelegate, JsonTokenStorage, Client>>,
     cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
 }
 
-pub(crate) fn create_gcal_scheduler(auth_file: String, cal_name: String) -> Result<GoogleScheduler, Box<dyn Error>> {
+pub(crate) fn create_gcal_scheduler(auth_file: String, cal_name: String, ui_tx: Sender<UICommand>) -> Result<GoogleScheduler, Box<dyn Error>> {
     let gcc = create_gcal_client(auth_file)?;
-    Ok(GoogleScheduler::new(cal_name, gcc))
+    Ok(GoogleScheduler::new(cal_name, gcc, ui_tx))
 }
 
 impl GoogleScheduler {
-    pub fn new(cal_name: String, hub: CalendarHub<Client, Authenticator<DefaultAuthenticatorDelegate, JsonTokenStorage, Client>>) -> GoogleScheduler {
+    pub fn new(cal_name: String, hub: CalendarHub<Client, Authenticator<DefaultAuthenticatorDelegate, JsonTokenStorage, Client>>, ui_tx: Sender<UICommand>) -> GoogleScheduler {
         GoogleScheduler {
             calendar_name: cal_name,
             hub,
-            cache: Vec::new()
+            cache: Vec::new(),
+            ui_tx
         }
     }
 }
@@ -35,7 +40,11 @@ impl Scheduler for GoogleScheduler {
         format!("google:{}", self.calendar_name)
     }
 
-    fn refresh(&mut self) -> Result<(), Box<dyn Error>> {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: false, delete: true, times: true, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
         let start_time = Local::now().add(Duration::minutes(-10)).to_rfc3339().clone();
         let end_time = Local::now().add(Duration::days(2)).to_rfc3339();
 
@@ -58,19 +67,53 @@ impl Scheduler for GoogleScheduler {
         self.cache.clone()
     }
 
-    fn add(&mut self, target: &String, due_date: Option<DateTime<Local>>) -> anyhow::Result<bool> {
-        // Not yet implemented
-        Ok(false)
+    fn add(&mut self, target: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        info!("Adding '{}' to Google calendar {}", target, self.calendar_name);
+        let (summary, location) = split_location(target);
+        let start = due_date.unwrap_or(Local::now());
+        let end = if is_all_day(start) { start } else { start + Duration::hours(1) };
+
+        let event = Event {
+            summary: Some(summary),
+            location,
+            start: Some(to_event_time(start, false)),
+            end: Some(to_event_time(end, true)),
+            ..Event::default()
+        };
+
+        self.hub.events().insert(event, self.calendar_name.as_ref()).doit()?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
     }
 
-    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> anyhow::Result<bool> {
-        // Not yet implemented
-        Ok(false)
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let item = match self.cache.iter().find(|i| i.id.native == *id) {
+            None => return Err(SchedulerError::NotFound(format!("No event {} in {}", id, self.calendar_name))),
+            Some(item) => item.clone()
+        };
+
+        // Patch only sends the fields we set, so the location and everything else stays put
+        let mut event = Event { summary: Some(description.clone()), ..Event::default() };
+        if let Some(due) = due_date {
+            let (start, end) = rescheduled_event_times(&item, due);
+            event.start = Some(start);
+            event.end = Some(end);
+        }
+
+        self.hub.events().patch(event, self.calendar_name.as_ref(), id.as_str()).doit()?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
     }
 
-    fn remove(&mut self, prefix: &String) -> anyhow::Result<bool> {
-        // Not yet implemented
-        Ok(false)
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Deleting event {} from {}", id, self.calendar_name);
+        self.hub.events().delete(self.calendar_name.as_ref(), id.as_str()).doit()?;
+
+        Ok(true)
     }
 }
 
@@ -79,16 +122,24 @@ fn cal_event_to_scheduled_item(account_id: String, e: &Event) -> Option<Schedule
     let description = e.summary.clone().unwrap_or("no desc".to_string());
     let place = format_location(e.location.clone());
 
+    // Without an id there's no way to change the event later
+    let id = e.id.as_ref().filter(|id| !id.is_empty())?;
+
     let start_time = event_start_time(e);
     let end_time = event_end_time(e);
     match (start_time, end_time) {
-        (Some(start_time), _) => Some(ScheduledItem::new(e.i_cal_uid.clone().unwrap_or("".to_string()),
-                                                         format!("google:{}", account_id),
-                                                         ScheduleItemType::Calendar,
-                                                         description,
-                                                         start_time,
-                                                         end_time,
-                                                         place)),
+        (Some(start_time), _) => {
+            let mut item = ScheduledItem::new(ItemId::new(&format!("google:{}", account_id), id),
+                                              ScheduleItemType::Calendar,
+                                              description,
+                                              start_time,
+                                              end_time,
+                                              place);
+            item.notes = e.description.clone();
+            item.link = e.html_link.clone();
+            item.all_day = e.start.as_ref().map_or(false, |s| s.date_time.is_none() && s.date.is_some());
+            Some(item)
+        },
         _ => None
     }
 }
@@ -130,18 +181,47 @@ fn convert_event_time(time: EventDateTime) -> DateTime<Local> {
         }
     };
 
-    let final_date = match datetime {
+    match datetime {
         Some(dt) => dt,
         None => match all_day {
             Some(ad) => ad.and_hms(23, 59, 59),
             None => Local::now()
         }
-    };
+    }
+}
 
-    if final_date.date() >= Local::now().date() {
-        final_date
+fn to_event_time(time: DateTime<Local>, is_end: bool) -> EventDateTime {
+    if is_all_day(time) {
+        // All-day events end on the following day
+        let date = if is_end { time.date().succ() } else { time.date() };
+        EventDateTime { date: Some(date.format("%Y-%m-%d").to_string()), ..EventDateTime::default() }
     } else {
-        Local::now().date().and_hms(23, 59, 59)
+        EventDateTime { date_time: Some(time.to_rfc3339()), ..EventDateTime::default() }
+    }
+}
+
+/// Where an event goes when it's moved to `due`, keeping its length. All-day events stay all-day,
+/// and timed ones keep their time of day when `due` is only a date.
+fn rescheduled_event_times(item: &ScheduledItem, due: DateTime<Local>) -> (EventDateTime, EventDateTime) {
+    if item.all_day {
+        // The end date is the day after the last one
+        let days = item.end_time.map_or(1, |end| (end.date() - item.start_time.date()).num_days().max(1));
+        let start = due.date().naive_local();
+        let date = |d: NaiveDate| EventDateTime { date: Some(d.format("%Y-%m-%d").to_string()), ..EventDateTime::default() };
+        (date(start), date(start + Duration::days(days)))
+    } else {
+        let length = item.end_time.map(|end| end - item.start_time).unwrap_or(Duration::hours(1));
+        let start = rescheduled(item.start_time, due);
+        let date_time = |t: DateTime<Local>| EventDateTime { date_time: Some(t.to_rfc3339()), ..EventDateTime::default() };
+        (date_time(start), date_time(start + length))
+    }
+}
+
+/// "Lunch with Sam @ Cafe Roma" -> ("Lunch with Sam", Some("Cafe Roma"))
+pub(crate) fn split_location(description: &String) -> (String, Option<String>) {
+    match description.rfind(" @ ") {
+        Some(idx) => (description[..idx].trim().to_string(), Some(description[idx + 3..].trim().to_string())),
+        None => (description.clone(), None)
     }
 }
 
@@ -166,6 +246,35 @@ mod tests {
         let after = "Warehouser 12".to_string();
         assert_eq!(strip_flexe(before), after);
     }
+
+    #[test]
+    fn locations_are_split_off_the_end_of_new_events() {
+        assert_eq!(("Lunch with Sam".to_string(), Some("Cafe Roma".to_string())), split_location(&"Lunch with Sam @ Cafe Roma".to_string()));
+        assert_eq!(("Email joe@example.com".to_string(), None), split_location(&"Email joe@example.com".to_string()));
+    }
+
+    #[test]
+    fn end_of_day_times_become_all_day_events() {
+        let time = Local.ymd(2026, 10, 20).and_hms(23, 59, 59);
+        assert_eq!(Some("2026-10-20".to_string()), to_event_time(time, false).date);
+        assert_eq!(Some("2026-10-21".to_string()), to_event_time(time, true).date);
+    }
+
+    #[test]
+    fn rescheduling_to_a_day_keeps_the_time_and_length() {
+        let meeting = ScheduledItem::new(ItemId::new("google:work", "abc"), ScheduleItemType::Calendar, "Standup".to_string(),
+                                         Local.ymd(2026, 10, 20).and_hms(10, 0, 0), Some(Local.ymd(2026, 10, 20).and_hms(10, 15, 0)), None);
+        let (start, end) = rescheduled_event_times(&meeting, Local.ymd(2026, 10, 22).and_hms(23, 59, 59));
+        assert_eq!((None, Some(Local.ymd(2026, 10, 22).and_hms(10, 0, 0).to_rfc3339())), (start.date, start.date_time));
+        assert_eq!(Some(Local.ymd(2026, 10, 22).and_hms(10, 15, 0).to_rfc3339()), end.date_time);
+
+        let mut holiday = meeting.clone();
+        holiday.all_day = true;
+        holiday.start_time = Local.ymd(2026, 10, 20).and_hms(23, 59, 59);
+        holiday.end_time = Some(Local.ymd(2026, 10, 22).and_hms(23, 59, 59));
+        let (start, end) = rescheduled_event_times(&holiday, Local.ymd(2026, 10, 27).and_hms(23, 59, 59));
+        assert_eq!((Some("2026-10-27".to_string()), Some("2026-10-29".to_string())), (start.date, end.date));
+    }
 }
 
 
@@ -0,0 +1,177 @@
+use hyper::Client;
+use hyper::header::{Headers, Authorization, Bearer, ContentType};
+use hyper::method::Method;
+use hyper::net::HttpsConnector;
+use hyper::status::StatusClass;
+use hyper::Url;
+use yup_oauth2::GetToken;
+use chrono::{NaiveDate, DateTime};
+use std::error::Error;
+use std::io::Read;
+use log::info;
+use crate::google_calendar_client::{GoogleAuthenticator, create_authenticator};
+use crate::scheduler_error::SchedulerError;
+
+// There's no google-tasks crate for the old hyper that google-calendar3 pins us to,
+// and the Tasks API is small enough that talking to it directly is simpler anyway.
+// See https://developers.google.com/tasks/reference/rest
+
+const URL_BASE: &str = "https://tasks.googleapis.com/tasks/v1/";
+const TASKS_SCOPE: &str = "https://www.googleapis.com/auth/tasks";
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct TaskList {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Deserialize,Debug)]
+#[serde(rename_all = "camelCase")]
+struct TaskLists {
+    #[serde(default)]
+    items: Vec<TaskList>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone,Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleTask {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>, // "needsAction" or "completed"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>, // RFC3339, but only the date part means anything
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_view_link: Option<String>,
+}
+
+#[derive(Deserialize,Debug)]
+#[serde(rename_all = "camelCase")]
+struct GoogleTasks {
+    #[serde(default)]
+    items: Vec<GoogleTask>,
+    next_page_token: Option<String>,
+}
+
+pub struct GoogleTasksClient {
+    auth: GoogleAuthenticator,
+    client: Client,
+}
+
+pub fn create_gtasks_client(auth_file: String) -> Result<GoogleTasksClient, Box<dyn Error>> {
+    Ok(GoogleTasksClient {
+        auth: create_authenticator(&auth_file)?,
+        client: Client::with_connector(HttpsConnector::new(hyper_rustls::TlsClient::new())),
+    })
+}
+
+impl GoogleTasksClient {
+    pub fn task_lists(&mut self) -> anyhow::Result<Vec<TaskList>> {
+        let mut lists = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = Url::parse(URL_BASE)?.join("users/@me/lists")?;
+            if let Some(token) = &page_token { url.query_pairs_mut().append_pair("pageToken", token); }
+
+            let page: TaskLists = serde_json::from_str(&self.send(Method::Get, url, None)?)?;
+            lists.extend(page.items);
+            page_token = page.next_page_token;
+            if page_token.is_none() { break; }
+        }
+        Ok(lists)
+    }
+
+    /// Open tasks in a list, subtasks included
+    pub fn tasks(&mut self, list_id: &str) -> anyhow::Result<Vec<GoogleTask>> {
+        let mut tasks = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = Url::parse(URL_BASE)?.join(&format!("lists/{}/tasks", list_id))?;
+            url.query_pairs_mut().
+                append_pair("showCompleted", "false").
+                append_pair("maxResults", "100");
+            if let Some(token) = &page_token { url.query_pairs_mut().append_pair("pageToken", token); }
+
+            let page: GoogleTasks = serde_json::from_str(&self.send(Method::Get, url, None)?)?;
+            tasks.extend(page.items);
+            page_token = page.next_page_token;
+            if page_token.is_none() { break; }
+        }
+        Ok(tasks)
+    }
+
+    pub fn insert(&mut self, list_id: &str, task: &GoogleTask) -> anyhow::Result<()> {
+        let url = Url::parse(URL_BASE)?.join(&format!("lists/{}/tasks", list_id))?;
+        info!("Google Tasks insert into {}: {:?}", list_id, task);
+        self.send(Method::Post, url, Some(serde_json::to_string(task)?))?;
+        Ok(())
+    }
+
+    /// Only the fields set on `task` are changed
+    pub fn patch(&mut self, list_id: &str, task_id: &str, task: &GoogleTask) -> anyhow::Result<()> {
+        let url = Url::parse(URL_BASE)?.join(&format!("lists/{}/tasks/{}", list_id, task_id))?;
+        info!("Google Tasks patch {}: {:?}", task_id, task);
+        self.send(Method::Patch, url, Some(serde_json::to_string(task)?))?;
+        Ok(())
+    }
+
+    fn send(&mut self, method: Method, url: Url, body: Option<String>) -> anyhow::Result<String> {
+        let token = match self.auth.token(&[TASKS_SCOPE]) {
+            Ok(token) => token,
+            Err(e) => return Err(SchedulerError::AuthExpired(format!("Could not authorize with Google Tasks: {}", e)).into())
+        };
+
+        let mut headers = Headers::new();
+        headers.set(Authorization(Bearer { token: token.access_token }));
+        headers.set(ContentType::json());
+
+        let mut request = self.client.request(method, url.clone()).headers(headers);
+        if let Some(body) = &body {
+            request = request.body(body.as_str());
+        }
+
+        let mut response = request.send().map_err(SchedulerError::from)?;
+        let mut contents = String::new();
+        response.read_to_string(&mut contents)?;
+
+        if response.status.class() != StatusClass::Success {
+            return Err(SchedulerError::from_status(response.status.to_u16(), format!("Google Tasks request to {} failed: {}", url, response.status)).into());
+        }
+
+        Ok(contents)
+    }
+}
+
+/// Tasks only have due dates, never times
+pub fn parse_due(due: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(due).ok().map(|d| d.naive_utc().date())
+}
+
+pub fn format_due(date: NaiveDate) -> String {
+    format!("{}T00:00:00.000Z", date.format("%Y-%m-%d"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn due_dates_round_trip() {
+        let date = NaiveDate::from_ymd(2026, 10, 20);
+        assert_eq!("2026-10-20T00:00:00.000Z", format_due(date));
+        assert_eq!(Some(date), parse_due(&format_due(date)));
+    }
+
+    #[test]
+    fn patches_only_send_what_changed() {
+        let patch = GoogleTask { status: Some("completed".to_string()), ..Default::default() };
+        assert_eq!(r#"{"status":"completed"}"#, serde_json::to_string(&patch).unwrap());
+    }
+}
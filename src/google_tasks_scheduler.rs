@@ -0,0 +1,143 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities, end_of_day};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::google_tasks_client::{GoogleTasksClient, GoogleTask, create_gtasks_client, parse_due, format_due};
+use crate::commands::UICommand;
+use chrono::{DateTime, Local};
+use std::error::Error;
+use std::sync::mpsc::Sender;
+use log::info;
+
+pub struct GoogleTasksScheduler {
+    list_name: Option<String>, // None means the default list
+    list_id: Option<String>,
+    client: GoogleTasksClient,
+    tasks: Vec<GoogleTask>,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_gtasks_scheduler(auth_file: String, list_name: Option<String>, ui_tx: Sender<UICommand>) -> Result<GoogleTasksScheduler, Box<dyn Error>> {
+    let client = create_gtasks_client(auth_file)?;
+    Ok(GoogleTasksScheduler::new(list_name, client, ui_tx))
+}
+
+impl GoogleTasksScheduler {
+    pub fn new(list_name: Option<String>, client: GoogleTasksClient, ui_tx: Sender<UICommand>) -> Self {
+        GoogleTasksScheduler { list_name, client, ui_tx, list_id: None, tasks: Vec::new(), cache: Vec::new() }
+    }
+
+    /// Look the list up by name the first time we need it
+    fn list_id(&mut self) -> SchedulerResult<String> {
+        if let Some(id) = &self.list_id { return Ok(id.clone()); }
+
+        let lists = self.client.task_lists()?;
+        let list = match &self.list_name {
+            // Google always puts the default list ("My Tasks") first
+            None => lists.first(),
+            Some(name) => lists.iter().find(|l| l.title == *name)
+        };
+
+        match list {
+            None => Err(SchedulerError::NotFound(format!("No Google Tasks list named '{}'", self.list_name.clone().unwrap_or_default()))),
+            Some(list) => {
+                self.list_id = Some(list.id.clone());
+                Ok(list.id.clone())
+            }
+        }
+    }
+
+    fn patch(&mut self, task_id: &str, patch: GoogleTask) -> SchedulerResult<()> {
+        let list_id = self.list_id()?;
+        Ok(self.client.patch(&list_id, task_id, &patch)?)
+    }
+}
+
+impl Scheduler for GoogleTasksScheduler {
+    fn id(&self) -> String {
+        format!("gtasks:{}", self.list_name.clone().unwrap_or("default".to_string()))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: true, delete: false, times: false, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let list_id = self.list_id()?;
+        self.tasks = self.client.tasks(&list_id)?;
+
+        let scheduler_id = self.id();
+        self.cache = self.tasks.iter().
+            filter_map(|t| task_to_scheduled_item(&scheduler_id, t)).
+            collect();
+
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        info!("Adding '{}' to Google Tasks list {}", description, self.id());
+        let task = GoogleTask {
+            title: Some(description.clone()),
+            due: due_date.map(|d| format_due(d.date().naive_local())),
+            ..Default::default()
+        };
+
+        let list_id = self.list_id()?;
+        self.client.insert(&list_id, &task)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let patch = GoogleTask {
+            title: Some(description.clone()),
+            due: due_date.map(|d| format_due(d.date().naive_local())),
+            ..Default::default()
+        };
+
+        self.patch(id, patch)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Completing task {} in Google Tasks list {}", id, self.id());
+        let patch = GoogleTask { status: Some("completed".to_string()), ..Default::default() };
+        self.patch(id, patch)?;
+        Ok(true)
+    }
+}
+
+fn task_to_scheduled_item(scheduler_id: &String, task: &GoogleTask) -> Option<ScheduledItem> {
+    let id = task.id.as_ref()?;
+    // Google leaves an empty placeholder around while someone is typing a new task
+    let title = task.title.as_ref().filter(|t| !t.is_empty())?;
+
+    let due_date = task.due.as_ref().and_then(|d| parse_due(d));
+    let due = match due_date {
+        Some(date) => end_of_day(date),
+        None => Local::today().and_hms(23, 59, 59)
+    };
+
+    let mut item = ScheduledItem::new(
+        ItemId::new(scheduler_id, id),
+        ScheduleItemType::Todo,
+        title.clone(),
+        due,
+        None,
+        None);
+    // Google Tasks only keeps the date, even though the API sends a time
+    item.all_day = true;
+    item.undated = due_date.is_none();
+    item.notes = task.notes.clone().filter(|n| !n.is_empty());
+    item.link = task.web_view_link.clone();
+    item.parent_id = task.parent.as_ref().map(|p| ItemId::new(scheduler_id, p));
+    Some(item)
+}
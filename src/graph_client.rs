@@ -0,0 +1,432 @@
+use hyper::Client;
+use hyper::header::{Headers, Authorization, Bearer, ContentType};
+use hyper::method::Method;
+use hyper::net::HttpsConnector;
+use hyper::status::StatusClass;
+use hyper::Url;
+use chrono::{DateTime, Utc, Duration};
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::sync::mpsc::{channel, Sender, Receiver, TryRecvError};
+use log::info;
+use crate::commands::UICommand;
+use crate::display::{PromptMessage, PromptMessageType};
+use crate::scheduler_error::SchedulerError;
+
+// Microsoft Graph (Outlook / Microsoft 365) client. Sign-in uses the OAuth device code
+// flow: we show a code, the user enters it at microsoft.com/devicelogin on any device,
+// and we poll until they're done. See
+// https://learn.microsoft.com/en-us/azure/active-directory/develop/v2-oauth2-device-code
+
+pub const DEFAULT_GRAPH_URL: &str = "https://graph.microsoft.com/v1.0/";
+pub const DEFAULT_LOGIN_URL: &str = "https://login.microsoftonline.com/";
+const SCOPES: &str = "offline_access Calendars.ReadWrite";
+
+/// config/{name}.json - from the app registration in the Azure portal
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct GraphCredentials {
+    pub client_id: String,
+    #[serde(default = "default_tenant")]
+    pub tenant: String,
+}
+
+fn default_tenant() -> String { "common".to_string() }
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct GraphToken {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl GraphToken {
+    fn expired(&self) -> bool {
+        // Leave a little slack so a token doesn't expire mid-request
+        self.expires_at < Utc::now() + Duration::minutes(1)
+    }
+}
+
+#[derive(Deserialize,Debug)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
+#[derive(Deserialize,Debug)]
+struct DeviceCodeResponse {
+    device_code: String,
+    message: String, // "To sign in, use a web browser to open the page ... and enter the code ..."
+    expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 { 5 }
+
+#[derive(Deserialize,Debug)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+/// Keeps the Graph token next to the credentials, the same way JsonTokenStorage does for Google
+pub struct GraphTokenStorage {
+    pub program_name: String,
+    pub db_dir: String,
+}
+
+impl GraphTokenStorage {
+    fn path(&self) -> PathBuf {
+        Path::new(&self.db_dir).join(&format!("{}-token.json", self.program_name))
+    }
+
+    pub fn get(&self) -> anyhow::Result<Option<GraphToken>> {
+        match fs::File::open(self.path()) {
+            Ok(f) => Ok(Some(serde_json::from_reader(f)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub fn set(&self, token: Option<&GraphToken>) -> anyhow::Result<()> {
+        match token {
+            None => match fs::remove_file(self.path()) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(())
+            },
+            Some(token) => {
+                fs::write(self.path(), serde_json::to_string_pretty(token)?)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The sign-in endpoints, separate from GraphClient so a sign-in can run on its own thread
+struct Login {
+    url: Url,
+    client_id: String,
+    client: Client,
+}
+
+/// How a device code sign-in running in the background is getting on
+enum SignInUpdate {
+    Code(String), // what to tell the user
+    Done(anyhow::Result<GraphToken>)
+}
+
+struct SignIn {
+    updates: Receiver<SignInUpdate>,
+    message: Option<String>,
+}
+
+pub struct GraphClient {
+    base_url: Url,
+    login: Login,
+    storage: GraphTokenStorage,
+    token: Option<GraphToken>,
+    signing_in: Option<SignIn>,
+    client: Client,
+    ui_tx: Sender<UICommand>,
+}
+
+impl GraphClient {
+    pub fn new(base_url: &str, login_url: &str, credentials: GraphCredentials, storage: GraphTokenStorage, ui_tx: Sender<UICommand>) -> anyhow::Result<GraphClient> {
+        let token = storage.get()?;
+        Ok(GraphClient {
+            base_url: Url::parse(&with_trailing_slash(base_url))?,
+            login: Login::new(Url::parse(&with_trailing_slash(login_url))?.join(&format!("{}/oauth2/v2.0/", credentials.tenant))?, credentials.client_id),
+            storage,
+            token,
+            signing_in: None,
+            client: Client::with_connector(HttpsConnector::new(hyper_rustls::TlsClient::new())),
+            ui_tx,
+        })
+    }
+
+    pub fn get(&mut self, path: &str, query: &[(&str, &str)]) -> anyhow::Result<serde_json::Value> {
+        let mut url = self.url(path)?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        self.send(Method::Get, url, None)
+    }
+
+    /// For following @odata.nextLink, which is already a full url
+    pub fn get_url(&mut self, url: &str) -> anyhow::Result<serde_json::Value> {
+        self.send(Method::Get, Url::parse(url)?, None)
+    }
+
+    pub fn post(&mut self, path: &str, body: &serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let url = self.url(path)?;
+        self.send(Method::Post, url, Some(body.to_string()))
+    }
+
+    pub fn patch(&mut self, path: &str, body: &serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let url = self.url(path)?;
+        self.send(Method::Patch, url, Some(body.to_string()))
+    }
+
+    pub fn delete(&mut self, path: &str) -> anyhow::Result<()> {
+        let url = self.url(path)?;
+        self.send(Method::Delete, url, None)?;
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> anyhow::Result<Url> {
+        Ok(self.base_url.join(path.trim_start_matches('/'))?)
+    }
+
+    fn send(&mut self, method: Method, url: Url, body: Option<String>) -> anyhow::Result<serde_json::Value> {
+        let access_token = self.access_token()?;
+
+        let mut headers = Headers::new();
+        headers.set(Authorization(Bearer { token: access_token }));
+        headers.set(ContentType::json());
+        // Everything comes back in UTC, so we don't need to know Windows timezone names
+        headers.set_raw("Prefer", vec![b"outlook.timezone=\"UTC\"".to_vec()]);
+
+        info!("Graph {} {}", method, url);
+        let mut request = self.client.request(method, url.clone()).headers(headers);
+        if let Some(body) = &body {
+            request = request.body(body.as_str());
+        }
+
+        let mut response = request.send().map_err(SchedulerError::from)?;
+        let mut contents = String::new();
+        response.read_to_string(&mut contents)?;
+
+        if response.status.class() != StatusClass::Success {
+            if response.status.to_u16() == 401 {
+                // Signed out or revoked elsewhere - sign in again next time
+                self.token = None;
+            }
+            let error = match retry_after(&response.headers) {
+                Some(wait) if response.status.to_u16() == 429 => SchedulerError::RateLimited(Some(wait)),
+                _ => SchedulerError::from_status(response.status.to_u16(), format!("Graph request to {} failed: {} {}", url, response.status, contents))
+            };
+            return Err(error.into());
+        }
+
+        if contents.trim().is_empty() {
+            Ok(serde_json::Value::Null)
+        } else {
+            Ok(serde_json::from_str(&contents)?)
+        }
+    }
+
+    /// Make sure we have a usable token, starting a sign-in if not. Never waits on the user.
+    pub fn sign_in(&mut self) -> anyhow::Result<()> {
+        self.access_token().map(|_| ())
+    }
+
+    /// Sign in with a device code. The user can take minutes over it, so it runs in the
+    /// background - until it's done, requests fail saying which code to enter, and the first
+    /// one after picks up the new token.
+    fn poll_sign_in(&mut self) -> anyhow::Result<String> {
+        if self.signing_in.is_none() {
+            self.signing_in = Some(self.start_sign_in());
+        }
+        let sign_in = self.signing_in.as_mut().unwrap();
+
+        let mut finished = None;
+        while finished.is_none() {
+            match sign_in.updates.try_recv() {
+                Ok(SignInUpdate::Code(message)) => sign_in.message = Some(message),
+                Ok(SignInUpdate::Done(result)) => finished = Some(result),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => finished = Some(Err(anyhow::anyhow!("Microsoft sign-in stopped unexpectedly")))
+            }
+        }
+
+        match finished {
+            // Win or lose, the next request without a token starts over
+            Some(result) => { self.signing_in = None; self.store(result?) }
+            None => {
+                let waiting = sign_in.message.clone().unwrap_or("Starting Microsoft sign-in".to_string());
+                Err(SchedulerError::Other(waiting).into())
+            }
+        }
+    }
+
+    fn start_sign_in(&self) -> SignIn {
+        let (tx, rx) = channel();
+        let login = Login::new(self.login.url.clone(), self.login.client_id.clone());
+        let ui_tx = self.ui_tx.clone();
+        thread::spawn(move || {
+            let result = login.device_code_sign_in(&ui_tx, &tx);
+            tx.send(SignInUpdate::Done(result)).ok();
+        });
+        SignIn { updates: rx, message: None }
+    }
+
+    /// A valid access token, refreshing it if it has run out, or signing in again if that's refused
+    fn access_token(&mut self) -> anyhow::Result<String> {
+        let refresh_token = match &self.token {
+            Some(token) if !token.expired() => return Ok(token.access_token.clone()),
+            Some(token) => token.refresh_token.clone(),
+            None => None
+        };
+
+        if let (Some(refresh_token), None) = (refresh_token, &self.signing_in) {
+            match self.login.refresh(&refresh_token) {
+                Ok(token) => return self.store(token),
+                Err(e) => match e.downcast_ref::<SchedulerError>() {
+                    Some(SchedulerError::AuthExpired(_)) => info!("Graph token refresh was refused, signing in again: {}", e),
+                    _ => return Err(e)
+                }
+            }
+        }
+        self.poll_sign_in()
+    }
+
+    fn store(&mut self, token: GraphToken) -> anyhow::Result<String> {
+        self.storage.set(Some(&token))?;
+        let access_token = token.access_token.clone();
+        self.token = Some(token);
+        Ok(access_token)
+    }
+}
+
+impl Login {
+    fn new(url: Url, client_id: String) -> Login {
+        Login { url, client_id, client: Client::with_connector(HttpsConnector::new(hyper_rustls::TlsClient::new())) }
+    }
+
+    fn refresh(&self, refresh_token: &str) -> anyhow::Result<GraphToken> {
+        let response = self.post_form("token", &[
+            ("client_id", &self.client_id),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("scope", SCOPES),
+        ])?;
+
+        match response {
+            Ok(token) => Ok(to_graph_token(token)),
+            Err(e) => Err(SchedulerError::AuthExpired(format!("{}: {}", e.error, e.error_description.unwrap_or_default())).into())
+        }
+    }
+
+    /// Show the user a code and wait for them to enter it - can take as long as the code lasts
+    fn device_code_sign_in(&self, ui_tx: &Sender<UICommand>, updates: &Sender<SignInUpdate>) -> anyhow::Result<GraphToken> {
+        let url = self.url.join("devicecode")?;
+        let body = form_encode(&[("client_id", &self.client_id), ("scope", SCOPES)]);
+        let (ok, contents) = self.send_form(url, body)?;
+        if !ok { anyhow::bail!("Could not start Microsoft sign-in: {}", contents); }
+        let device: DeviceCodeResponse = serde_json::from_str(&contents)?;
+
+        info!("{}", device.message);
+        updates.send(SignInUpdate::Code(device.message.clone())).ok();
+        ui_tx.send(UICommand::Toast(PromptMessage::new(device.message.clone(),
+                                                       std::time::Duration::from_secs(device.expires_in),
+                                                       PromptMessageType::Normal))).ok();
+
+        let mut interval = device.interval;
+        let give_up = Utc::now() + Duration::seconds(device.expires_in as i64);
+        while Utc::now() < give_up {
+            thread::sleep(std::time::Duration::from_secs(interval));
+
+            let response = self.post_form("token", &[
+                ("client_id", &self.client_id),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", &device.device_code),
+            ])?;
+
+            match response {
+                Ok(token) => return Ok(to_graph_token(token)),
+                Err(e) if e.error == "authorization_pending" => {}
+                Err(e) if e.error == "slow_down" => interval += 5,
+                Err(e) => return Err(SchedulerError::AuthExpired(format!("Microsoft sign-in failed: {}", e.error_description.unwrap_or(e.error))).into())
+            }
+        }
+
+        Err(SchedulerError::AuthExpired("Microsoft sign-in timed out".to_string()).into())
+    }
+
+    fn post_form(&self, endpoint: &str, params: &[(&str, &str)]) -> anyhow::Result<Result<TokenResponse, ErrorResponse>> {
+        let (ok, contents) = self.send_form(self.url.join(endpoint)?, form_encode(params))?;
+        if ok {
+            Ok(Ok(serde_json::from_str(&contents)?))
+        } else {
+            Ok(Err(serde_json::from_str(&contents)?))
+        }
+    }
+
+    fn send_form(&self, url: Url, body: String) -> anyhow::Result<(bool, String)> {
+        let mut headers = Headers::new();
+        headers.set(ContentType::form_url_encoded());
+
+        let mut response = self.client.post(url).headers(headers).body(body.as_str()).send().map_err(SchedulerError::from)?;
+        let mut contents = String::new();
+        response.read_to_string(&mut contents)?;
+        Ok((response.status.class() == StatusClass::Success, contents))
+    }
+}
+
+/// Graph sends Retry-After as a number of seconds when it throttles us
+fn retry_after(headers: &Headers) -> Option<std::time::Duration> {
+    let value = headers.get_raw("Retry-After")?.first()?;
+    let secs = std::str::from_utf8(value).ok()?.trim().parse().ok()?;
+    Some(std::time::Duration::from_secs(secs))
+}
+
+fn to_graph_token(response: TokenResponse) -> GraphToken {
+    GraphToken {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+        expires_at: Utc::now() + Duration::seconds(response.expires_in),
+    }
+}
+
+fn with_trailing_slash(url: &str) -> String {
+    if url.ends_with('/') { url.to_string() } else { format!("{}/", url) }
+}
+
+fn form_encode(params: &[(&str, &str)]) -> String {
+    // Borrow Url's encoder rather than pull in another crate for it
+    let mut url = Url::parse("http://localhost/").unwrap();
+    url.query_pairs_mut().extend_pairs(params);
+    url.query().unwrap_or("").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn form_encodes_params() {
+        assert_eq!("scope=offline_access+Calendars.ReadWrite&code=a%2Fb",
+                   form_encode(&[("scope", SCOPES), ("code", "a/b")]));
+    }
+
+    #[test]
+    fn tokens_expire_a_little_early() {
+        let token = GraphToken { access_token: "a".to_string(), refresh_token: None, expires_at: Utc::now() + Duration::seconds(30) };
+        assert!(token.expired());
+    }
+
+    #[test]
+    fn signing_in_never_waits_on_the_user() {
+        let (ui_tx, _ui_rx) = channel();
+        let storage = GraphTokenStorage { program_name: "test".to_string(), db_dir: "/nonexistent".to_string() };
+        let credentials = GraphCredentials { client_id: "id".to_string(), tenant: "common".to_string() };
+        let mut client = GraphClient::new(DEFAULT_GRAPH_URL, "http://127.0.0.1:9/", credentials, storage, ui_tx).unwrap();
+
+        let started = std::time::Instant::now();
+        assert!(client.sign_in().is_err());
+        assert!(client.get("me/calendar", &[]).is_err());
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reads_retry_after_seconds() {
+        let mut headers = Headers::new();
+        headers.set_raw("Retry-After", vec![b"12".to_vec()]);
+        assert_eq!(Some(std::time::Duration::from_secs(12)), retry_after(&headers));
+        assert_eq!(None, retry_after(&Headers::new()));
+    }
+}
@@ -0,0 +1,591 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+// Just enough iCalendar (RFC 5545) to read and write VEVENTs and VTODOs.
+// Components are kept as a tree of raw properties so that anything we don't
+// understand survives a round trip back to the server untouched.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+impl Property {
+    pub fn new(name: &str, value: String) -> Property {
+        Property { name: name.to_string(), params: vec![], value }
+    }
+
+    pub fn param(&self, name: &str) -> Option<&String> {
+        self.params.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v)
+    }
+
+    fn to_line(&self) -> String {
+        let mut line = self.name.clone();
+        for (k, v) in &self.params {
+            line.push_str(&format!(";{}={}", k, v));
+        }
+        line.push(':');
+        line.push_str(&self.value);
+        line
+    }
+}
+
+impl Component {
+    pub fn new(name: &str) -> Component {
+        Component { name: name.to_string(), properties: vec![], components: vec![] }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn get_all(&self, name: &str) -> Vec<&Property> {
+        self.properties.iter().filter(|p| p.name.eq_ignore_ascii_case(name)).collect()
+    }
+
+    pub fn value(&self, name: &str) -> Option<&String> {
+        self.get(name).map(|p| &p.value)
+    }
+
+    /// Unescaped value of a TEXT property, like SUMMARY or LOCATION.
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.value(name).map(|v| unescape_text(v))
+    }
+
+    /// Replaces every instance of property `name` with `property`.
+    pub fn set(&mut self, property: Property) {
+        match self.properties.iter().position(|p| p.name.eq_ignore_ascii_case(&property.name)) {
+            Some(idx) => {
+                self.remove(&property.name);
+                self.properties.insert(idx, property);
+            }
+            None => self.properties.push(property)
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.properties.retain(|p| !p.name.eq_ignore_ascii_case(name));
+    }
+
+    pub fn to_ical(&self) -> String {
+        let mut out = format!("BEGIN:{}\r\n", self.name);
+        for p in &self.properties {
+            out.push_str(&fold(&p.to_line()));
+        }
+        for c in &self.components {
+            out.push_str(&c.to_ical());
+        }
+        out.push_str(&format!("END:{}\r\n", self.name));
+        out
+    }
+}
+
+/// Parse an iCalendar document into its top level components (usually a single VCALENDAR).
+pub fn parse(text: &str) -> anyhow::Result<Vec<Component>> {
+    let mut stack: Vec<Component> = vec![];
+    let mut top = vec![];
+
+    for line in unfold(text) {
+        let property = match parse_line(&line) {
+            Some(p) => p,
+            None => continue
+        };
+
+        match property.name.to_uppercase().as_str() {
+            "BEGIN" => stack.push(Component::new(&property.value.to_uppercase())),
+            "END" => {
+                let component = match stack.pop() {
+                    Some(c) => c,
+                    None => anyhow::bail!("Unexpected END:{} in calendar data", property.value)
+                };
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => top.push(component)
+                }
+            }
+            _ => {
+                if let Some(current) = stack.last_mut() {
+                    current.properties.push(property);
+                }
+            }
+        }
+    }
+
+    if !stack.is_empty() {
+        anyhow::bail!("Calendar data ended inside a {}", stack.last().unwrap().name);
+    }
+
+    Ok(top)
+}
+
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for raw in text.split('\n') {
+        let raw = raw.trim_end_matches('\r');
+        if (raw.starts_with(' ') || raw.starts_with('\t')) && !lines.is_empty() {
+            lines.last_mut().unwrap().push_str(&raw[1..]);
+        } else if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+fn fold(line: &str) -> String {
+    // Lines SHOULD be no longer than 75 octets, not counting the line break
+    let mut out = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn parse_line(line: &str) -> Option<Property> {
+    // The value starts at the first ':' that isn't inside a quoted parameter
+    let mut in_quotes = false;
+    let mut split_at = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => { split_at = Some(i); break; }
+            _ => {}
+        }
+    }
+
+    let split_at = split_at?;
+    let (head, value) = (&line[..split_at], &line[split_at + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_uppercase();
+    let params = parts.
+        filter_map(|p| {
+            let mut kv = p.splitn(2, '=');
+            Some((kv.next()?.to_uppercase(), kv.next()?.trim_matches('"').to_string()))
+        }).
+        collect();
+
+    Some(Property { name, params, value: value.to_string() })
+}
+
+pub fn unescape_text(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+pub fn escape_text(value: &str) -> String {
+    value.replace('\\', "\\\\").
+        replace(';', "\\;").
+        replace(',', "\\,").
+        replace('\n', "\\n")
+}
+
+/// Convert a DATE or DATE-TIME property into local time.
+///
+/// Times with a TZID are treated as local time, since we don't carry a timezone database.
+/// Date-only values land at the end of the day, like all-day events elsewhere.
+pub fn property_to_datetime(prop: &Property) -> Option<DateTime<Local>> {
+    let value = prop.value.trim();
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+    }
+
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Local.from_local_datetime(&naive).earliest();
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    Local.from_local_date(&date).earliest().map(|d| d.and_hms(23, 59, 59))
+}
+
+pub fn is_date_only(prop: &Property) -> bool {
+    prop.param("VALUE").map_or(false, |v| v.eq_ignore_ascii_case("DATE")) || !prop.value.contains('T')
+}
+
+pub fn datetime_property(name: &str, time: DateTime<Local>) -> Property {
+    Property::new(name, format_utc(time))
+}
+
+pub fn date_property(name: &str, time: DateTime<Local>) -> Property {
+    Property {
+        name: name.to_string(),
+        params: vec![("VALUE".to_string(), "DATE".to_string())],
+        value: time.format("%Y%m%d").to_string()
+    }
+}
+
+pub fn format_utc(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Recurrence rules. Only the parts of RRULE that real world feeds actually use are
+// supported: FREQ, INTERVAL, COUNT, UNTIL, BYDAY, BYMONTHDAY and BYMONTH. Rules using
+// anything else aren't expanded at all - just the first occurrence beats the wrong dates.
+
+#[derive(Debug, Clone, PartialEq)]
+enum Frequency { Daily, Weekly, Monthly, Yearly }
+
+#[derive(Debug, Clone)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<DateTime<Local>>,
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+// Give up on rules that never produce anything in the window, rather than spinning forever
+const MAX_PERIODS: u32 = 10_000;
+
+fn parse_rrule(value: &str) -> Option<RecurrenceRule> {
+    let mut rule = RecurrenceRule { frequency: Frequency::Daily, interval: 1, count: None, until: None, by_day: vec![], by_month_day: vec![], by_month: vec![] };
+    let mut has_freq = false;
+
+    for part in value.split(';') {
+        let mut kv = part.splitn(2, '=');
+        let (key, val) = (kv.next()?.to_uppercase(), kv.next().unwrap_or(""));
+        match key.as_str() {
+            "FREQ" => {
+                has_freq = true;
+                rule.frequency = match val.to_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None // HOURLY and friends aren't worth the trouble for an agenda
+                };
+            }
+            "INTERVAL" => rule.interval = val.parse().ok().filter(|i| *i > 0)?,
+            "COUNT" => rule.count = Some(val.parse().ok()?),
+            "UNTIL" => rule.until = property_to_datetime(&Property::new("UNTIL", val.to_string())),
+            "BYDAY" => rule.by_day = val.split(',').filter_map(parse_weekday).collect(),
+            "BYMONTHDAY" => rule.by_month_day = val.split(',').filter_map(|d| d.parse().ok()).collect(),
+            "BYMONTH" => rule.by_month = val.split(',').filter_map(|m| m.parse().ok()).filter(|m| (1..=12).contains(m)).collect(),
+            "WKST" => {} // only matters alongside parts we don't support anyway
+            _ => return None // BYSETPOS, BYWEEKNO, BYYEARDAY...
+        }
+    }
+
+    // Weekdays across a whole year, rather than within given months, isn't supported either
+    if rule.frequency == Frequency::Yearly && !rule.by_day.is_empty() && rule.by_month.is_empty() { return None; }
+
+    if has_freq { Some(rule) } else { None }
+}
+
+fn parse_weekday(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let (ordinal, day) = value.split_at(value.len().checked_sub(2)?);
+    let weekday = match day.to_uppercase().as_str() {
+        "MO" => Weekday::Mon, "TU" => Weekday::Tue, "WE" => Weekday::Wed, "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri, "SA" => Weekday::Sat, "SU" => Weekday::Sun,
+        _ => return None
+    };
+    let ordinal = if ordinal.is_empty() { None } else { Some(ordinal.trim_start_matches('+').parse().ok()?) };
+    Some((ordinal, weekday))
+}
+
+fn add_months(year: i32, month: u32, months: u32) -> (i32, u32) {
+    let total = (month - 1) + months;
+    (year + (total / 12) as i32, total % 12 + 1)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = add_months(year, month, 1);
+    NaiveDate::from_ymd(next_year, next_month, 1).pred().day()
+}
+
+fn weekdays_in_month(year: i32, month: u32, weekday: Weekday, ordinal: Option<i32>) -> Vec<NaiveDate> {
+    let all: Vec<NaiveDate> = (1..=days_in_month(year, month)).
+        map(|d| NaiveDate::from_ymd(year, month, d)).
+        filter(|d| d.weekday() == weekday).
+        collect();
+
+    match ordinal {
+        None => all,
+        Some(n) if n > 0 => all.get(n as usize - 1).cloned().into_iter().collect(),
+        Some(n) => all.len().checked_sub((-n) as usize).and_then(|i| all.get(i)).cloned().into_iter().collect()
+    }
+}
+
+/// The days of one month a monthly or yearly rule picks. With both BYMONTHDAY and BYDAY a day has
+/// to match both, so FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13 is every Friday the 13th.
+fn month_dates(rule: &RecurrenceRule, year: i32, month: u32, start_day: u32) -> Vec<NaiveDate> {
+    let last = days_in_month(year, month) as i32;
+    let month_days: Vec<NaiveDate> = rule.by_month_day.iter().
+        map(|d| if *d < 0 { last + d + 1 } else { *d }).
+        filter_map(|d| NaiveDate::from_ymd_opt(year, month, d as u32)).
+        collect();
+    let weekdays: Vec<NaiveDate> = rule.by_day.iter().
+        flat_map(|(n, wd)| weekdays_in_month(year, month, *wd, *n)).
+        collect();
+
+    match (rule.by_month_day.is_empty(), rule.by_day.is_empty()) {
+        (false, false) => month_days.into_iter().filter(|d| weekdays.contains(d)).collect(),
+        (false, true) => month_days,
+        (true, false) => weekdays,
+        (true, true) => NaiveDate::from_ymd_opt(year, month, start_day).into_iter().collect()
+    }
+}
+
+/// The first period that can reach into a window starting at `from`, so long-running rules don't
+/// use up MAX_PERIODS before they get there. Rules with a COUNT are walked from the start, since
+/// every occurrence before the window still counts towards it.
+fn first_period(rule: &RecurrenceRule, start: NaiveDate, from: NaiveDate) -> u32 {
+    if rule.count.is_some() || from <= start {
+        return 0;
+    }
+
+    let elapsed = match rule.frequency {
+        Frequency::Daily => (from - start).num_days(),
+        Frequency::Weekly => (from - start).num_weeks(),
+        Frequency::Monthly => (from.year() - start.year()) as i64 * 12 + from.month() as i64 - start.month() as i64,
+        Frequency::Yearly => (from.year() - start.year()) as i64
+    };
+    // Start a period early, in case the one before still runs into the window
+    (elapsed / rule.interval as i64 - 1).max(0) as u32
+}
+
+/// Candidate dates for the `period`th repetition of the rule, in order.
+fn period_dates(rule: &RecurrenceRule, start: NaiveDate, period: u32) -> Vec<NaiveDate> {
+    let step = period * rule.interval;
+    let mut dates = match rule.frequency {
+        Frequency::Daily => vec![start + Duration::days(step as i64)],
+        Frequency::Weekly => {
+            let week_start = start - Duration::days(start.weekday().num_days_from_monday() as i64) + Duration::weeks(step as i64);
+            if rule.by_day.is_empty() {
+                vec![start + Duration::weeks(step as i64)]
+            } else {
+                rule.by_day.iter().map(|(_, wd)| week_start + Duration::days(wd.num_days_from_monday() as i64)).collect()
+            }
+        }
+        Frequency::Monthly => {
+            let (year, month) = add_months(start.year(), start.month(), step);
+            month_dates(rule, year, month, start.day())
+        }
+        Frequency::Yearly => {
+            let year = start.year() + step as i32;
+            let months = if rule.by_month.is_empty() { vec![start.month()] } else { rule.by_month.clone() };
+            months.iter().flat_map(|month| month_dates(rule, year, *month, start.day())).collect()
+        }
+    };
+
+    // For the other frequencies BYMONTH only narrows things down
+    if rule.frequency != Frequency::Yearly && !rule.by_month.is_empty() {
+        dates.retain(|d| rule.by_month.contains(&d.month()));
+    }
+
+    dates.sort();
+    dates.dedup();
+    dates
+}
+
+/// Start times of every occurrence of `event` that overlaps the window.
+/// Events without an RRULE are simply checked against the window.
+pub fn occurrences(event: &Component, window_start: DateTime<Local>, window_end: DateTime<Local>) -> Vec<DateTime<Local>> {
+    let start = match event.get("DTSTART").and_then(property_to_datetime) {
+        Some(s) => s,
+        None => return vec![]
+    };
+    let duration = event.get("DTEND").and_then(property_to_datetime).map_or(Duration::zero(), |end| end - start);
+    let in_window = |t: &DateTime<Local>| *t <= window_end && *t + duration >= window_start;
+
+    let rule = match event.value("RRULE").and_then(|r| parse_rrule(r)) {
+        Some(rule) => rule,
+        None => return if in_window(&start) { vec![start] } else { vec![] }
+    };
+
+    let excluded: Vec<DateTime<Local>> = event.get_all("EXDATE").iter().
+        flat_map(|p| p.value.split(',').map(move |v| Property { name: p.name.clone(), params: p.params.clone(), value: v.to_string() })).
+        filter_map(|p| property_to_datetime(&p)).
+        collect();
+
+    let mut found = vec![];
+    let mut count = 0;
+    let first = first_period(&rule, start.date().naive_local(), (window_start - duration).date().naive_local());
+    for period in first..first + MAX_PERIODS {
+        for date in period_dates(&rule, start.date().naive_local(), period) {
+            let time = match Local.from_local_datetime(&date.and_time(start.time())).earliest() {
+                Some(t) if t >= start => t,
+                _ => continue
+            };
+
+            if time > window_end || rule.until.map_or(false, |u| time > u) || rule.count.map_or(false, |c| count >= c) {
+                return found;
+            }
+
+            count += 1;
+            if in_window(&time) && !excluded.contains(&time) {
+                found.push(time);
+            }
+        }
+    }
+
+    found
+}
+
+/// Every occurrence of these events that overlaps the window, with its start time. Modified
+/// instances (the ones with a RECURRENCE-ID) take the place of the occurrence they were made from.
+pub fn expand<'a>(events: &[&'a Component], window_start: DateTime<Local>, window_end: DateTime<Local>) -> Vec<(&'a Component, DateTime<Local>)> {
+    let mut found = vec![];
+    for event in events {
+        let overrides: Vec<DateTime<Local>> = events.iter().
+            filter(|o| o.get("RECURRENCE-ID").is_some() && o.value("UID") == event.value("UID")).
+            filter_map(|o| property_to_datetime(o.get("RECURRENCE-ID").unwrap())).
+            collect();
+
+        for start_time in occurrences(event, window_start, window_end) {
+            if event.get("RECURRENCE-ID").is_none() && overrides.contains(&start_time) { continue; }
+            found.push((*event, start_time));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TODO: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\nUID:abc-123\r\nSUMMARY:Buy milk\\, eggs\r\nDUE;VALUE=DATE:20261020\r\nDESCRIPTION:A very long description that goes on and on and on\r\n  past the folding limit\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn parses_nested_components_and_unfolds_lines() {
+        let cal = parse(TODO).unwrap();
+        let todos = cal[0].components.iter().filter(|c| c.name == "VTODO").collect::<Vec<_>>();
+        assert_eq!(1, todos.len());
+        assert_eq!(Some("Buy milk, eggs".to_string()), todos[0].text("SUMMARY"));
+        assert_eq!("A very long description that goes on and on and on past the folding limit",
+                   todos[0].value("DESCRIPTION").unwrap());
+    }
+
+    #[test]
+    fn date_only_values_land_at_the_end_of_the_day() {
+        let cal = parse(TODO).unwrap();
+        let due = cal[0].components[0].get("DUE").unwrap().clone();
+        assert!(is_date_only(&due));
+        assert_eq!(Local.ymd(2026, 10, 20).and_hms(23, 59, 59), property_to_datetime(&due).unwrap());
+    }
+
+    #[test]
+    fn utc_values_are_converted_to_local_time() {
+        let prop = Property::new("DTSTART", "20261020T153000Z".to_string());
+        assert_eq!(Utc.ymd(2026, 10, 20).and_hms(15, 30, 0).with_timezone(&Local), property_to_datetime(&prop).unwrap());
+    }
+
+    #[test]
+    fn round_trips_through_to_ical() {
+        let cal = parse(TODO).unwrap();
+        let reparsed = parse(&cal[0].to_ical()).unwrap();
+        assert_eq!(cal, reparsed);
+    }
+
+    #[test]
+    fn quoted_params_may_contain_colons() {
+        let prop = parse_line("ORGANIZER;CN=\"Joe: the boss\":mailto:joe@example.com").unwrap();
+        assert_eq!("Joe: the boss", prop.param("CN").unwrap());
+        assert_eq!("mailto:joe@example.com", prop.value);
+    }
+
+    fn event(rule: &str) -> Component {
+        let text = format!("BEGIN:VEVENT\r\nUID:1\r\nDTSTART:20261001T090000\r\nDTEND:20261001T100000\r\n{}\r\nEND:VEVENT\r\n", rule);
+        parse(&text).unwrap().remove(0)
+    }
+
+    fn at(day: u32, month: u32) -> DateTime<Local> {
+        Local.ymd(2026, month, day).and_hms(9, 0, 0)
+    }
+
+    #[test]
+    fn events_without_rules_are_checked_against_the_window() {
+        let e = event("SUMMARY:Once");
+        assert_eq!(vec![at(1, 10)], occurrences(&e, at(1, 10), at(2, 10)));
+        assert!(occurrences(&e, at(2, 10), at(3, 10)).is_empty());
+    }
+
+    #[test]
+    fn daily_rules_respect_interval_and_count() {
+        let e = event("RRULE:FREQ=DAILY;INTERVAL=2;COUNT=3");
+        assert_eq!(vec![at(1, 10), at(3, 10), at(5, 10)], occurrences(&e, at(1, 10), at(31, 10)));
+    }
+
+    #[test]
+    fn weekly_rules_expand_by_day() {
+        // 2026-10-01 is a Thursday
+        let e = event("RRULE:FREQ=WEEKLY;BYDAY=TU,TH;UNTIL=20261010T000000Z");
+        assert_eq!(vec![at(1, 10), at(6, 10), at(8, 10)], occurrences(&e, at(1, 10), at(31, 10)));
+    }
+
+    #[test]
+    fn monthly_rules_handle_ordinal_weekdays() {
+        let e = event("RRULE:FREQ=MONTHLY;BYDAY=-1FR");
+        assert_eq!(vec![at(30, 10), at(27, 11)], occurrences(&e, at(1, 10), at(30, 11)));
+    }
+
+    #[test]
+    fn yearly_rules_pick_weekdays_within_months() {
+        // US Thanksgiving
+        let e = event("RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH");
+        assert_eq!(vec![at(26, 11)], occurrences(&e, at(1, 10), at(31, 12)));
+    }
+
+    #[test]
+    fn month_days_and_weekdays_must_both_match() {
+        let e = event("RRULE:FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13");
+        assert_eq!(vec![at(13, 11)], occurrences(&e, at(1, 10), at(31, 12)));
+    }
+
+    #[test]
+    fn old_rules_still_reach_the_window() {
+        let text = "BEGIN:VEVENT\r\nUID:1\r\nDTSTART:19900101T090000\r\nDTEND:19900101T100000\r\nRRULE:FREQ=DAILY\r\nEND:VEVENT\r\n";
+        let e = parse(text).unwrap().remove(0);
+        assert_eq!(vec![at(1, 10), at(2, 10)], occurrences(&e, at(1, 10), at(2, 10)));
+    }
+
+    #[test]
+    fn rules_with_unsupported_parts_only_show_the_first_occurrence() {
+        let e = event("RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1");
+        assert_eq!(vec![at(1, 10)], occurrences(&e, at(1, 10), at(31, 12)));
+        let e = event("RRULE:FREQ=YEARLY;BYDAY=20MO");
+        assert_eq!(vec![at(1, 10)], occurrences(&e, at(1, 10), at(31, 12)));
+    }
+
+    #[test]
+    fn excluded_dates_are_skipped() {
+        let e = event("RRULE:FREQ=DAILY\r\nEXDATE:20261002T090000,20261003T090000");
+        assert_eq!(vec![at(1, 10), at(4, 10)], occurrences(&e, at(1, 10), at(4, 10)));
+    }
+
+    #[test]
+    fn only_occurrences_in_the_window_are_returned() {
+        let e = event("RRULE:FREQ=WEEKLY");
+        assert_eq!(vec![at(15, 10)], occurrences(&e, at(14, 10), at(16, 10)));
+    }
+}
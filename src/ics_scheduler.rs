@@ -0,0 +1,157 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::ical::{self, Component};
+use chrono::{DateTime, Local, Duration};
+use hyper::Client;
+use hyper::net::HttpsConnector;
+use hyper::status::StatusClass;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::ops::Add;
+use log::info;
+
+/// Read-only calendar loaded from an .ics file or URL - holidays, on-call rotations and the like.
+pub struct IcsScheduler {
+    name: String,
+    source: String,
+    cache: Vec<ScheduledItem>,
+}
+
+pub(crate) fn create_ics_scheduler(name: String, source: String) -> Result<IcsScheduler, Box<dyn Error>> {
+    Ok(IcsScheduler::new(name, source))
+}
+
+impl IcsScheduler {
+    pub fn new(name: String, source: String) -> Self {
+        IcsScheduler { name, source, cache: Vec::new() }
+    }
+
+    fn fetch(&self) -> SchedulerResult<String> {
+        // Plenty of feeds are handed out as webcal:// links, but they're really just https
+        let url = self.source.replacen("webcal://", "https://", 1);
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Ok(fs::read_to_string(&self.source)?);
+        }
+
+        let client = Client::with_connector(HttpsConnector::new(hyper_rustls::TlsClient::new()));
+        let mut response = client.get(url.as_str()).send()?;
+        if response.status.class() != StatusClass::Success {
+            return Err(SchedulerError::from_status(response.status.to_u16(), format!("Could not load {}: {}", self.source, response.status)));
+        }
+
+        let mut contents = String::new();
+        response.read_to_string(&mut contents)?;
+        Ok(contents)
+    }
+
+    fn read_only(&self) -> SchedulerError {
+        SchedulerError::ReadOnly(self.id())
+    }
+}
+
+impl Scheduler for IcsScheduler {
+    fn id(&self) -> String {
+        format!("ics:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::read_only()
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let window_start = Local::now().add(Duration::minutes(-10));
+        let window_end = Local::now().add(Duration::days(2));
+
+        let calendars = ical::parse(&self.fetch()?).map_err(|e| SchedulerError::Parse(e.to_string()))?;
+        let events: Vec<&Component> = calendars.iter().
+            flat_map(|c| c.components.iter()).
+            filter(|c| c.name == "VEVENT").
+            collect();
+
+        let scheduler_id = self.id();
+        let items: Vec<ScheduledItem> = ical::expand(&events, window_start, window_end).into_iter().
+            map(|(event, start_time)| event_to_scheduled_item(&scheduler_id, event, start_time)).
+            collect();
+
+        info!("Loaded {} events from {}", items.len(), self.source);
+        self.cache = items;
+
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, _description: &String, _due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        Err(self.read_only())
+    }
+
+    fn update(&mut self, _id: &String, _description: &String, _due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        Err(self.read_only())
+    }
+
+    fn remove(&mut self, _id: &String) -> SchedulerResult<bool> {
+        Err(self.read_only())
+    }
+}
+
+fn event_to_scheduled_item(scheduler_id: &String, event: &Component, start_time: DateTime<Local>) -> ScheduledItem {
+    let uid = event.value("UID").cloned().unwrap_or_default();
+    let duration = match (event.get("DTSTART").and_then(ical::property_to_datetime), event.get("DTEND").and_then(ical::property_to_datetime)) {
+        (Some(start), Some(end)) => Some(end - start),
+        _ => None
+    };
+
+    let mut item = ScheduledItem::new(
+        ItemId::new(scheduler_id, &format!("{}/{}", uid, ical::format_utc(start_time))),
+        ScheduleItemType::Calendar,
+        event.text("SUMMARY").unwrap_or("no desc".to_string()),
+        start_time,
+        duration.map(|d| start_time + d),
+        event.text("LOCATION"));
+    item.all_day = event.get("DTSTART").map_or(false, ical::is_date_only);
+    item.notes = event.text("DESCRIPTION");
+    item.link = event.value("URL").cloned();
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn schedule(text: &str, window_start: DateTime<Local>, window_end: DateTime<Local>) -> Vec<ScheduledItem> {
+        let calendars = ical::parse(&format!("BEGIN:VCALENDAR\r\n{}END:VCALENDAR\r\n", text)).unwrap();
+        let events: Vec<&Component> = calendars[0].components.iter().collect();
+        ical::expand(&events, window_start, window_end).into_iter().
+            map(|(event, start_time)| event_to_scheduled_item(&"ics:test".to_string(), event, start_time)).
+            collect()
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Local> {
+        Local.ymd(2026, 10, day).and_hms(hour, 0, 0)
+    }
+
+    #[test]
+    fn modified_occurrences_replace_the_ones_they_came_from() {
+        let items = schedule("BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup\r\nDTSTART:20261001T090000\r\nDTEND:20261001T091500\r\nRRULE:FREQ=DAILY\r\nEND:VEVENT\r\n\
+                              BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Late standup\r\nRECURRENCE-ID:20261002T090000\r\nDTSTART:20261002T110000\r\nDTEND:20261002T111500\r\nEND:VEVENT\r\n",
+                             at(1, 0), at(3, 0));
+
+        let mut found: Vec<(String, DateTime<Local>)> = items.iter().map(|i| (i.description.clone(), i.start_time)).collect();
+        found.sort_by_key(|(_, start)| *start);
+        assert_eq!(vec![("Standup".to_string(), at(1, 9)), ("Late standup".to_string(), at(2, 11))], found);
+        assert_ne!(items[0].id, items[1].id);
+    }
+
+    #[test]
+    fn excluded_occurrences_are_left_out() {
+        let items = schedule("BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup\r\nDTSTART:20261001T090000\r\nDTEND:20261001T091500\r\nRRULE:FREQ=DAILY\r\nEXDATE:20261002T090000\r\nEND:VEVENT\r\n",
+                             at(1, 0), at(3, 23));
+
+        assert_eq!(vec![at(1, 9), at(3, 9)], items.iter().map(|i| i.start_time).collect::<Vec<_>>());
+        assert_eq!(Some(at(3, 9) + Duration::minutes(15)), items[1].end_time);
+    }
+}
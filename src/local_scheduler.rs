@@ -0,0 +1,228 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities, is_all_day};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::commands::UICommand;
+use crate::schedule_cache::write_atomically;
+use chrono::{DateTime, Local};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use log::info;
+
+/// On-disk format for the local scheduler. Whether it's read/written as TOML or JSON
+/// depends on the extension of the configured file.
+#[derive(Serialize,Deserialize,Debug,Clone,Default)]
+pub struct LocalFile {
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub items: Vec<LocalItem>
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct LocalItem {
+    pub id: u64,
+    pub description: String,
+    #[serde(default = "default_item_type")]
+    pub item_type: ScheduleItemType,
+    pub start_time: DateTime<Local>,
+    pub end_time: Option<DateTime<Local>>,
+    pub place: Option<String>,
+    pub priority: Option<u8>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub link: Option<String>
+}
+
+fn default_item_type() -> ScheduleItemType { ScheduleItemType::Todo }
+
+pub struct LocalScheduler {
+    name: String,
+    path: String,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_local_scheduler(name: String, path: String, ui_tx: Sender<UICommand>) -> Result<LocalScheduler, Box<dyn Error>> {
+    let scheduler = LocalScheduler::new(name, path, ui_tx);
+    // Fail early on a file we can't understand, rather than on the first refresh
+    scheduler.load()?;
+    Ok(scheduler)
+}
+
+impl LocalScheduler {
+    pub fn new(name: String, path: String, ui_tx: Sender<UICommand>) -> Self {
+        LocalScheduler { name, path, ui_tx, cache: Vec::new() }
+    }
+
+    fn is_json(&self) -> bool {
+        Path::new(&self.path).extension().map_or(false, |ext| ext == "json")
+    }
+
+    fn load(&self) -> anyhow::Result<LocalFile> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            // No file yet just means nothing has been added yet.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(LocalFile::default()),
+            Err(e) => return Err(e.into())
+        };
+
+        let file = if self.is_json() {
+            serde_json::from_str(&contents)?
+        } else {
+            toml::from_str(&contents)?
+        };
+
+        Ok(file)
+    }
+
+    fn save(&self, file: &LocalFile) -> anyhow::Result<()> {
+        let contents = if self.is_json() {
+            serde_json::to_string_pretty(file)?
+        } else {
+            toml::to_string_pretty(file)?
+        };
+
+        // Everything we know about lives in this one file, so never leave it half written
+        write_atomically(&self.path, &contents)
+    }
+
+    fn to_scheduled_item(&self, item: &LocalItem) -> ScheduledItem {
+        let mut scheduled = ScheduledItem::new(
+            ItemId::new(&self.id(), &item.id.to_string()),
+            item.item_type.clone(),
+            item.description.clone(),
+            item.start_time,
+            item.end_time,
+            item.place.clone());
+        scheduled.priority = item.priority;
+        scheduled.tags = item.tags.clone();
+        scheduled.notes = item.notes.clone();
+        scheduled.link = item.link.clone();
+        scheduled.all_day = is_all_day(item.start_time);
+        scheduled
+    }
+}
+
+impl Scheduler for LocalScheduler {
+    fn id(&self) -> String {
+        format!("local:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: false, delete: true, times: true, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let file = self.load()?;
+        self.cache = file.items.iter().map(|i| self.to_scheduled_item(i)).collect();
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        info!("Adding '{}' to local file {}", description, self.path);
+        let mut file = self.load()?;
+        file.next_id += 1;
+        file.items.push(LocalItem {
+            id: file.next_id,
+            description: description.clone(),
+            item_type: ScheduleItemType::Todo,
+            start_time: due_date.unwrap_or(Local::now()),
+            end_time: None,
+            place: None,
+            priority: None,
+            tags: vec![],
+            notes: None,
+            link: None
+        });
+        self.save(&file)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let mut file = self.load()?;
+        let item = match file.items.iter_mut().find(|i| i.id.to_string() == *id) {
+            None => return Err(SchedulerError::NotFound(format!("Item {} is no longer in {}", id, self.path))),
+            Some(item) => item
+        };
+
+        item.description = description.clone();
+        if let Some(due_date) = due_date {
+            // Keep the length of events the same when moving them around
+            item.end_time = item.end_time.map(|end| due_date + (end - item.start_time));
+            item.start_time = due_date;
+        }
+
+        self.save(&file)?;
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Looking for task {} in local file {}", id, self.path);
+        let mut file = self.load()?;
+        match file.items.iter().position(|i| i.id.to_string() == *id) {
+            None => return Err(SchedulerError::NotFound(format!("Item {} is no longer in {}", id, self.path))),
+            Some(idx) => file.items.remove(idx)
+        };
+
+        self.save(&file)?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::env;
+    use std::sync::mpsc::channel;
+
+    fn round_trip(file_name: &str) {
+        let path = env::temp_dir().join(format!("todor-{}-{}", std::process::id(), file_name));
+        let _ = fs::remove_file(&path);
+        let (ui_tx, _ui_rx) = channel();
+        let mut local = LocalScheduler::new("test".to_string(), path.to_string_lossy().to_string(), ui_tx);
+        let due = Local.ymd(2026, 10, 20).and_hms(9, 0, 0);
+        local.add(&"Call mum".to_string(), Some(due)).unwrap();
+        local.add(&"Buy milk".to_string(), None).unwrap();
+        local.refresh().unwrap();
+        let schedule = local.schedule();
+        assert_eq!(vec!["Call mum", "Buy milk"], schedule.iter().map(|i| i.description.as_str()).collect::<Vec<_>>());
+        assert_eq!(due, schedule[0].start_time);
+
+        let moved = Local.ymd(2026, 10, 21).and_hms(23, 59, 59);
+        local.update(&schedule[0].id.native, &"Call mum back".to_string(), Some(moved)).unwrap();
+        local.remove(&schedule[1].id.native).unwrap();
+        local.refresh().unwrap();
+        let schedule = local.schedule();
+        assert_eq!(1, schedule.len());
+        assert_eq!("Call mum back", schedule[0].description);
+        assert_eq!(moved, schedule[0].start_time);
+        assert!(schedule[0].all_day);
+
+        assert!(matches!(local.remove(&"99".to_string()), Err(SchedulerError::NotFound(_))));
+        fs::remove_file(&local.path).unwrap();
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        round_trip("local.toml");
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        round_trip("local.json");
+    }
+}
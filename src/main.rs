@@ -20,11 +20,29 @@ use crate::display::{ScheduleWindow, WindowStack};
 
 mod google_calendar_client;
 mod google_scheduler;
+mod google_tasks_client;
+mod google_tasks_scheduler;
+mod graph_client;
+mod outlook_scheduler;
 mod scheduled_item;
+mod scheduler_error;
+mod schedule_cache;
+mod outbox;
 mod schedule_formatter;
 mod schedule_colorer;
 mod todoist_scheduler;
 mod todoist_client;
+mod todoist_sync_client;
+mod local_scheduler;
+mod ical;
+mod caldav_client;
+mod caldav_scheduler;
+mod ics_scheduler;
+mod todotxt_scheduler;
+mod markdown_scheduler;
+mod org_scheduler;
+mod text_lines;
+mod plugin_scheduler;
 mod commands;
 mod tasks;
 mod display;
@@ -61,6 +79,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                 match cmd {
                     UICommand::Exit => { break; } // time to quit!
                     UICommand::Add(scheduler_id, task) => { cmd_tx.send(ScheduleCommand::Add(scheduler_id, task)); }
+                    UICommand::Schedulers(schedulers) => {
+                        command_executor.set_capabilities(&schedulers);
+                        windows.handle_ui_command(UICommand::Schedulers(schedulers), &mut stdout);
+                    }
                     UICommand::Execute(command) => { command_executor.execute_command(&command, None); }
                     UICommand::ExecuteWithItem(command, item) => { command_executor.execute_command(&command, Some(&item)); }
 
@@ -0,0 +1,264 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities, end_of_day};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::commands::UICommand;
+use chrono::{DateTime, Local, NaiveDate};
+use regex::Regex;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use crate::text_lines::{line_ids, line_path, find_line, write_lines};
+use log::info;
+
+/// A "- [ ] ..." checklist item found in a Markdown note
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub checked: bool,
+    pub description: String,
+    pub due: Option<NaiveDate>,
+    pub tags: Vec<String>,
+}
+
+pub struct MarkdownScheduler {
+    name: String,
+    dir: String,
+    inbox: String,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_markdown_scheduler(name: String, dir: String, inbox: Option<String>, ui_tx: Sender<UICommand>) -> Result<MarkdownScheduler, Box<dyn Error>> {
+    if !Path::new(&dir).is_dir() {
+        return Err(format!("Markdown notes directory '{}' does not exist", dir).into());
+    }
+    Ok(MarkdownScheduler::new(name, dir, inbox.unwrap_or("todor.md".to_string()), ui_tx))
+}
+
+impl MarkdownScheduler {
+    pub fn new(name: String, dir: String, inbox: String, ui_tx: Sender<UICommand>) -> Self {
+        MarkdownScheduler { name, dir, inbox, ui_tx, cache: Vec::new() }
+    }
+
+    fn read_lines(path: &Path) -> anyhow::Result<Vec<String>> {
+        Ok(fs::read_to_string(path)?.lines().map(|l| l.to_string()).collect())
+    }
+
+    /// Re-find a line we saw on the last refresh - the note may have been edited since.
+    fn locate(&self, id: &String) -> anyhow::Result<Option<(PathBuf, Vec<String>, usize)>> {
+        // Only ever one of our own files
+        let path = match line_path(id).filter(|p| p.starts_with(&self.dir) && p.is_file()) {
+            None => return Ok(None),
+            Some(path) => path.to_path_buf()
+        };
+
+        let lines = Self::read_lines(&path)?;
+        Ok(find_line(id, &path, &lines).map(|idx| (path, lines, idx)))
+    }
+}
+
+impl Scheduler for MarkdownScheduler {
+    fn id(&self) -> String {
+        format!("markdown:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: true, delete: false, times: false, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let mut files = vec![];
+        find_notes(Path::new(&self.dir), &mut files)?;
+        files.sort();
+
+        let scheduler_id = self.id();
+        let mut items = vec![];
+        for path in files {
+            let lines = match Self::read_lines(&path) {
+                Ok(lines) => lines,
+                Err(e) => { info!("Could not read {:?}: {}", path, e); continue; }
+            };
+
+            let ids = line_ids(&path, &lines);
+            for (line, id) in lines.iter().zip(ids.iter()) {
+                match parse_line(line) {
+                    Some(item) if !item.checked => items.push(item_to_scheduled_item(&scheduler_id, id, &item)),
+                    _ => {}
+                }
+            }
+        }
+        self.cache = items;
+
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let path = Path::new(&self.dir).join(&self.inbox);
+        info!("Adding '{}' to {:?}", description, path);
+
+        let mut lines = if path.exists() { Self::read_lines(&path)? } else { vec![] };
+        let mut line = format!("- [ ] {}", description);
+        if let Some(due) = due_date {
+            line = set_due(&line, due.date().naive_local());
+        }
+        lines.push(line);
+        write_lines(&path, &lines)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let (path, mut lines, idx) = match self.locate(id)? {
+            None => return Err(SchedulerError::NotFound(format!("Item {} is no longer in {}", id, self.dir))),
+            Some(found) => found
+        };
+
+        let mut line = lines[idx].clone();
+        if let Some(item) = parse_line(&line) {
+            if item.description != *description {
+                line = line.replacen(&item.description, description, 1);
+            }
+        }
+        if let Some(due) = due_date {
+            line = set_due(&line, due.date().naive_local());
+        }
+        lines[idx] = line;
+        write_lines(&path, &lines)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Looking for item {} in {}", id, self.dir);
+        let (path, mut lines, idx) = match self.locate(id)? {
+            None => return Err(SchedulerError::NotFound(format!("Item {} is no longer in {}", id, self.dir))),
+            Some(found) => found
+        };
+
+        lines[idx] = check_line(&lines[idx]);
+        write_lines(&path, &lines)?;
+
+        Ok(true)
+    }
+}
+
+fn find_notes(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        // Skip things like .git, .obsidian and .trash
+        if path.file_name().map_or(false, |n| n.to_string_lossy().starts_with('.')) { continue; }
+
+        if path.is_dir() {
+            find_notes(&path, files)?;
+        } else if path.extension().map_or(false, |ext| ext == "md" || ext == "markdown") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn item_to_scheduled_item(scheduler_id: &String, id: &String, item: &ChecklistItem) -> ScheduledItem {
+    let due = match item.due {
+        Some(date) => end_of_day(date),
+        None => Local::today().and_hms(23, 59, 59)
+    };
+
+    let mut scheduled = ScheduledItem::new(
+        ItemId::new(scheduler_id, id),
+        ScheduleItemType::Todo,
+        item.description.clone(),
+        due,
+        None,
+        None);
+    scheduled.all_day = true;
+    scheduled.undated = item.due.is_none();
+    scheduled.tags = item.tags.clone();
+    scheduled
+}
+
+lazy_static! {
+    static ref CHECKBOX_REGEX: Regex = Regex::new(r"^(\s*[-*+]\s+\[)([ xX])(\]\s+)(.*)$").unwrap();
+    // Obsidian Tasks style "📅 2026-10-20", or todo.txt style "due:2026-10-20"
+    static ref DUE_REGEX: Regex = Regex::new(r"(?:📅\s*|due:)(\d{4}-\d{2}-\d{2})").unwrap();
+}
+
+pub fn parse_line(line: &str) -> Option<ChecklistItem> {
+    let cap = CHECKBOX_REGEX.captures(line)?;
+    let checked = &cap[2] != " ";
+    let text = &cap[4];
+
+    let due = DUE_REGEX.captures(text).and_then(|d| NaiveDate::parse_from_str(&d[1], "%Y-%m-%d").ok());
+    let description = DUE_REGEX.replace_all(text, "").split_whitespace().collect::<Vec<_>>().join(" ");
+    let tags = description.split_whitespace().
+        filter(|w| w.len() > 1 && w.starts_with('#')).
+        map(|w| w.to_string()).
+        collect();
+
+    Some(ChecklistItem { checked, description, due, tags })
+}
+
+fn set_due(line: &str, due: NaiveDate) -> String {
+    let date = due.format("%Y-%m-%d").to_string();
+    if DUE_REGEX.is_match(line) {
+        // Keep whichever style the note already uses
+        let cap = DUE_REGEX.captures(line).unwrap();
+        let old = cap.get(0).unwrap().as_str();
+        line.replacen(old, &old.replace(&cap[1], &date), 1)
+    } else {
+        format!("{} 📅 {}", line.trim_end(), date)
+    }
+}
+
+fn check_line(line: &str) -> String {
+    CHECKBOX_REGEX.replace(line, "${1}x${3}${4}").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_obsidian_style_due_dates() {
+        let item = parse_line("  - [ ] Send the slides to #work folks 📅 2026-10-20").unwrap();
+        assert_eq!(ChecklistItem {
+            checked: false,
+            description: "Send the slides to #work folks".to_string(),
+            due: Some(NaiveDate::from_ymd(2026, 10, 20)),
+            tags: vec!["#work".to_string()],
+        }, item);
+    }
+
+    #[test]
+    fn parses_todotxt_style_due_dates() {
+        let item = parse_line("* [X] Book a room due:2026-10-21").unwrap();
+        assert!(item.checked);
+        assert_eq!("Book a room", item.description);
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 21)), item.due);
+    }
+
+    #[test]
+    fn ignores_plain_list_items() {
+        assert_eq!(None, parse_line("- Just a note"));
+        assert_eq!(None, parse_line("[ ] not a list item"));
+    }
+
+    #[test]
+    fn checking_keeps_the_rest_of_the_line() {
+        assert_eq!("  - [x] Book a room 📅 2026-10-21", check_line("  - [ ] Book a room 📅 2026-10-21"));
+    }
+
+    #[test]
+    fn setting_the_due_date_keeps_the_existing_style() {
+        let day = NaiveDate::from_ymd(2026, 10, 22);
+        assert_eq!("- [ ] Book a room due:2026-10-22", set_due("- [ ] Book a room due:2026-10-21", day));
+        assert_eq!("- [ ] Book a room 📅 2026-10-22", set_due("- [ ] Book a room 📅 2026-10-21", day));
+        assert_eq!("- [ ] Book a room 📅 2026-10-22", set_due("- [ ] Book a room", day));
+    }
+}
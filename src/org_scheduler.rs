@@ -0,0 +1,332 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::commands::UICommand;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use regex::Regex;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use crate::text_lines::{line_ids, line_path, find_line, write_lines};
+use log::info;
+
+/// An Org timestamp like <2026-10-20 Tue 10:00-11:00>
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrgTimestamp {
+    pub date: NaiveDate,
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+}
+
+/// A TODO headline, along with its planning line if it has one
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrgTodo {
+    pub level: usize,
+    pub priority: Option<char>,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub scheduled: Option<OrgTimestamp>,
+    pub deadline: Option<OrgTimestamp>,
+}
+
+pub struct OrgScheduler {
+    name: String,
+    path: String,
+    inbox: String,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_org_scheduler(name: String, path: String, inbox: Option<String>, ui_tx: Sender<UICommand>) -> Result<OrgScheduler, Box<dyn Error>> {
+    if !Path::new(&path).exists() {
+        return Err(format!("Org file or directory '{}' does not exist", path).into());
+    }
+    Ok(OrgScheduler::new(name, path, inbox.unwrap_or("todor.org".to_string()), ui_tx))
+}
+
+impl OrgScheduler {
+    pub fn new(name: String, path: String, inbox: String, ui_tx: Sender<UICommand>) -> Self {
+        OrgScheduler { name, path, inbox, ui_tx, cache: Vec::new() }
+    }
+
+    fn read_lines(path: &Path) -> anyhow::Result<Vec<String>> {
+        Ok(fs::read_to_string(path)?.lines().map(|l| l.to_string()).collect())
+    }
+
+    /// New headlines go to the configured file, or the inbox file when configured with a directory
+    fn add_target(&self) -> PathBuf {
+        let path = Path::new(&self.path);
+        if path.is_dir() { path.join(&self.inbox) } else { path.to_path_buf() }
+    }
+
+    /// Re-find a headline we saw on the last refresh - the file may have been edited since.
+    fn locate(&self, id: &String) -> anyhow::Result<Option<(PathBuf, Vec<String>, usize)>> {
+        // Only ever one of our own files
+        let path = match line_path(id).filter(|p| p.starts_with(&self.path) && p.is_file()) {
+            None => return Ok(None),
+            Some(path) => path.to_path_buf()
+        };
+
+        let lines = Self::read_lines(&path)?;
+        Ok(find_line(id, &path, &lines).map(|idx| (path, lines, idx)))
+    }
+}
+
+impl Scheduler for OrgScheduler {
+    fn id(&self) -> String {
+        format!("org:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: true, delete: false, times: false, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let mut files = vec![];
+        let path = Path::new(&self.path);
+        if path.is_dir() { find_org_files(path, &mut files)?; } else { files.push(path.to_path_buf()); }
+        files.sort();
+
+        let scheduler_id = self.id();
+        let mut items = vec![];
+        for path in files {
+            let lines = match Self::read_lines(&path) {
+                Ok(lines) => lines,
+                Err(e) => { info!("Could not read {:?}: {}", path, e); continue; }
+            };
+
+            let ids = line_ids(&path, &lines);
+            for (line_idx, line) in lines.iter().enumerate() {
+                if let Some(todo) = parse_todo(line, lines.get(line_idx + 1).map(|l| l.as_str())) {
+                    items.push(todo_to_scheduled_item(&scheduler_id, &ids[line_idx], &todo));
+                }
+            }
+        }
+        self.cache = items;
+
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let path = self.add_target();
+        info!("Adding '{}' to {:?}", description, path);
+
+        let mut lines = if path.exists() { Self::read_lines(&path)? } else { vec![] };
+        lines.push(format!("* TODO {}", description));
+        if let Some(due) = due_date {
+            lines.push(format!("  SCHEDULED: {}", format_date(due.date().naive_local())));
+        }
+        write_lines(&path, &lines)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let (path, mut lines, idx) = match self.locate(id)? {
+            None => return Err(SchedulerError::NotFound(format!("Headline {} is no longer in {}", id, self.path))),
+            Some(found) => found
+        };
+
+        if let Some(todo) = parse_todo(&lines[idx], None) {
+            if todo.title != *description {
+                lines[idx] = lines[idx].replacen(&todo.title, description, 1);
+            }
+        }
+
+        if let Some(due) = due_date {
+            let date = due.date().naive_local();
+            if lines.get(idx + 1).map_or(false, |l| is_planning_line(l)) {
+                lines[idx + 1] = reschedule_planning_line(&lines[idx + 1], date);
+            } else {
+                lines.insert(idx + 1, format!("{}SCHEDULED: {}", body_indent(&lines[idx]), format_date(date)));
+            }
+        }
+        write_lines(&path, &lines)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Looking for item {} in {}", id, self.path);
+        let (path, mut lines, idx) = match self.locate(id)? {
+            None => return Err(SchedulerError::NotFound(format!("Headline {} is no longer in {}", id, self.path))),
+            Some(found) => found
+        };
+
+        let closed = format!("CLOSED: {}", format_inactive(Local::now().naive_local()));
+        lines[idx] = TODO_KEYWORD_REGEX.replace(&lines[idx], "${1}DONE").to_string();
+        if lines.get(idx + 1).map_or(false, |l| is_planning_line(l)) {
+            // CLOSED goes first on the planning line, same as Emacs does it
+            let planning = lines[idx + 1].trim_start().to_string();
+            lines[idx + 1] = format!("{}{} {}", body_indent(&lines[idx]), closed, planning);
+        } else {
+            lines.insert(idx + 1, format!("{}{}", body_indent(&lines[idx]), closed));
+        }
+        write_lines(&path, &lines)?;
+
+        Ok(true)
+    }
+}
+
+fn find_org_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.file_name().map_or(false, |n| n.to_string_lossy().starts_with('.')) { continue; }
+
+        if path.is_dir() {
+            find_org_files(&path, files)?;
+        } else if path.extension().map_or(false, |ext| ext == "org") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn todo_to_scheduled_item(scheduler_id: &String, id: &String, todo: &OrgTodo) -> ScheduledItem {
+    let timestamp = todo.scheduled.as_ref().or(todo.deadline.as_ref());
+    let (start_time, end_time) = match timestamp {
+        None => (Local::today().and_hms(23, 59, 59), None),
+        Some(ts) => {
+            let start = to_local(ts.date, ts.start.unwrap_or(NaiveTime::from_hms(23, 59, 59)));
+            (start, ts.end.map(|end| to_local(ts.date, end)))
+        }
+    };
+
+    let mut item = ScheduledItem::new(
+        ItemId::new(scheduler_id, id),
+        ScheduleItemType::Todo,
+        todo.title.clone(),
+        start_time,
+        end_time,
+        None);
+    // [#A] is the most urgent
+    item.priority = todo.priority.map(|p| p as u8 - b'A' + 1);
+    item.tags = todo.tags.clone();
+    item.all_day = timestamp.map_or(true, |ts| ts.start.is_none());
+    item.undated = timestamp.is_none();
+    item
+}
+
+fn to_local(date: NaiveDate, time: NaiveTime) -> DateTime<Local> {
+    Local.from_local_datetime(&date.and_time(time)).earliest().unwrap_or(Local::now())
+}
+
+lazy_static! {
+    static ref HEADLINE_REGEX: Regex = Regex::new(r"^(\*+)\s+TODO\s+(?:\[#([A-Z])\]\s+)?(.*?)(?:\s+(:[\w@#%:]+:))?\s*$").unwrap();
+    static ref TODO_KEYWORD_REGEX: Regex = Regex::new(r"^(\*+\s+)TODO").unwrap();
+    static ref PLANNING_REGEX: Regex = Regex::new(r"^\s*(SCHEDULED|DEADLINE|CLOSED):").unwrap();
+    static ref TIMESTAMP_REGEX: Regex = Regex::new(
+        r"(SCHEDULED|DEADLINE):\s*<(\d{4}-\d{2}-\d{2})(?:\s+[^\s\d>]+)?(?:\s+(\d{1,2}:\d{2})(?:-(\d{1,2}:\d{2}))?)?[^>]*>").unwrap();
+    static ref TIMESTAMP_DATE_REGEX: Regex = Regex::new(r"<\d{4}-\d{2}-\d{2}(?:\s+[^\s\d>]+)?").unwrap();
+}
+
+/// Parse a TODO headline. The line after it is checked for SCHEDULED/DEADLINE timestamps.
+pub fn parse_todo(headline: &str, next_line: Option<&str>) -> Option<OrgTodo> {
+    let cap = HEADLINE_REGEX.captures(headline)?;
+    let tags = cap.get(4).map_or(vec![], |t| {
+        t.as_str().split(':').filter(|t| !t.is_empty()).map(|t| t.to_string()).collect()
+    });
+
+    let mut todo = OrgTodo {
+        level: cap[1].len(),
+        priority: cap.get(2).and_then(|p| p.as_str().chars().next()),
+        title: cap[3].to_string(),
+        tags,
+        scheduled: None,
+        deadline: None,
+    };
+
+    if let Some(planning) = next_line.filter(|l| is_planning_line(l)) {
+        for ts in TIMESTAMP_REGEX.captures_iter(planning) {
+            let date = match NaiveDate::parse_from_str(&ts[2], "%Y-%m-%d") {
+                Ok(date) => date,
+                Err(_) => continue
+            };
+            let parse_time = |m: Option<regex::Match>| m.and_then(|t| NaiveTime::parse_from_str(t.as_str(), "%H:%M").ok());
+            let timestamp = OrgTimestamp { date, start: parse_time(ts.get(3)), end: parse_time(ts.get(4)) };
+            match &ts[1] {
+                "SCHEDULED" => todo.scheduled = Some(timestamp),
+                _ => todo.deadline = Some(timestamp)
+            }
+        }
+    }
+
+    Some(todo)
+}
+
+fn is_planning_line(line: &str) -> bool {
+    PLANNING_REGEX.is_match(line)
+}
+
+fn body_indent(headline: &str) -> String {
+    // Line up with the headline's text, the way org-indent-mode does
+    " ".repeat(headline.chars().take_while(|c| *c == '*').count() + 1)
+}
+
+/// Move SCHEDULED to the new date, or DEADLINE if that's all there is
+fn reschedule_planning_line(line: &str, date: NaiveDate) -> String {
+    let keyword = if line.contains("SCHEDULED:") || !line.contains("DEADLINE:") { "SCHEDULED" } else { "DEADLINE" };
+    let existing = TIMESTAMP_REGEX.captures_iter(line).find(|c| &c[1] == keyword).map(|c| c[0].to_string());
+    match existing {
+        // Only the date moves - the time, repeater and warning period all stay as they were
+        Some(old) => {
+            let new_timestamp = TIMESTAMP_DATE_REGEX.replace(&old, format!("<{}", date.format("%Y-%m-%d %a")).as_str());
+            line.replacen(&old, &new_timestamp, 1)
+        }
+        None => format!("{} {}: {}", line.trim_end(), keyword, format_date(date))
+    }
+}
+
+fn format_date(date: NaiveDate) -> String {
+    format!("<{}>", date.format("%Y-%m-%d %a"))
+}
+
+fn format_inactive(time: NaiveDateTime) -> String {
+    format!("[{}]", time.format("%Y-%m-%d %a %H:%M"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_headlines_with_priority_and_tags() {
+        let todo = parse_todo("** TODO [#A] Review the budget   :work:finance:", None).unwrap();
+        assert_eq!(2, todo.level);
+        assert_eq!(Some('A'), todo.priority);
+        assert_eq!("Review the budget", todo.title);
+        assert_eq!(vec!["work".to_string(), "finance".to_string()], todo.tags);
+    }
+
+    #[test]
+    fn ignores_other_headlines() {
+        assert_eq!(None, parse_todo("* DONE Review the budget", None));
+        assert_eq!(None, parse_todo("* Meeting notes", None));
+    }
+
+    #[test]
+    fn reads_the_planning_line() {
+        let todo = parse_todo("* TODO Standup", Some("  SCHEDULED: <2026-10-20 Tue 10:00-10:15> DEADLINE: <2026-10-21 Wed>")).unwrap();
+        let time = |h, m| Some(NaiveTime::from_hms(h, m, 0));
+        assert_eq!(Some(OrgTimestamp { date: NaiveDate::from_ymd(2026, 10, 20), start: time(10, 0), end: time(10, 15) }), todo.scheduled);
+        assert_eq!(Some(OrgTimestamp { date: NaiveDate::from_ymd(2026, 10, 21), start: None, end: None }), todo.deadline);
+    }
+
+    #[test]
+    fn rescheduling_moves_scheduled_before_deadline() {
+        let day = NaiveDate::from_ymd(2026, 10, 22);
+        assert_eq!("  SCHEDULED: <2026-10-22 Thu 10:00> DEADLINE: <2026-10-25 Sun>",
+                   reschedule_planning_line("  SCHEDULED: <2026-10-20 Tue 10:00> DEADLINE: <2026-10-25 Sun>", day));
+        assert_eq!("  DEADLINE: <2026-10-22 Thu>", reschedule_planning_line("  DEADLINE: <2026-10-25 Sun>", day));
+        assert_eq!("  SCHEDULED: <2026-10-22 Thu 09:00 +1w -2d>", reschedule_planning_line("  SCHEDULED: <2026-10-20 Tue 09:00 +1w -2d>", day));
+        assert_eq!("  CLOSED: [2026-10-19 Mon 08:00] SCHEDULED: <2026-10-22 Thu>", reschedule_planning_line("  CLOSED: [2026-10-19 Mon 08:00]", day));
+    }
+}
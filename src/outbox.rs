@@ -0,0 +1,250 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, ScheduleItemType, is_all_day};
+use crate::schedule_cache::write_atomically;
+use chrono::{DateTime, Local};
+use std::fmt;
+use std::fs;
+use std::io;
+use log::info;
+
+pub const OUTBOX_FILE: &str = "config/outbox.json";
+
+// Native ids of the stand-ins we show for adds that haven't gone through yet
+const PENDING_PREFIX: &str = "pending:";
+
+/// A change the user made that hasn't reached its scheduler yet.
+/// Due dates are worked out when the change is made, so "tomorrow" still means the same day when it's replayed.
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
+pub enum PendingWrite {
+    Add { scheduler: String, description: String, due: DateTime<Local> },
+    Close { id: ItemId, description: String },
+    Reschedule { id: ItemId, description: String, due: DateTime<Local> },
+}
+
+/// A write in the outbox. The id names its stand-in, and stays put however the writes around it come and go.
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
+pub struct Queued {
+    pub id: u64,
+    pub write: PendingWrite,
+}
+
+/// Writes waiting for their scheduler to come back, kept on disk so they survive a restart
+#[derive(Serialize,Deserialize,Debug,Default)]
+pub struct Outbox {
+    #[serde(skip)]
+    path: Option<String>, // None keeps it in memory only
+    next_id: u64, // saved too, so ids aren't handed out twice across a restart
+    writes: Vec<Queued>, // oldest first
+}
+
+impl PendingWrite {
+    pub fn scheduler(&self) -> &str {
+        match self {
+            PendingWrite::Add { scheduler, .. } => scheduler,
+            PendingWrite::Close { id, .. } | PendingWrite::Reschedule { id, .. } => &id.scheduler
+        }
+    }
+
+    /// The existing item this changes, if it isn't a new one
+    pub fn item_id(&self) -> Option<&ItemId> {
+        match self {
+            PendingWrite::Add { .. } => None,
+            PendingWrite::Close { id, .. } | PendingWrite::Reschedule { id, .. } => Some(id)
+        }
+    }
+}
+
+// Reads as "<write> <scheduler id>", e.g. "add 'Buy milk' to todoist:home"
+impl fmt::Display for PendingWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PendingWrite::Add { description, .. } => write!(f, "add '{}' to", description),
+            PendingWrite::Close { description, .. } => write!(f, "close '{}' in", description),
+            PendingWrite::Reschedule { description, .. } => write!(f, "reschedule '{}' in", description),
+        }
+    }
+}
+
+impl Outbox {
+    /// Whatever was still waiting when we last stopped
+    pub fn load(path: &str) -> Outbox {
+        let mut outbox = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                info!("Ignoring unreadable outbox {}: {}", path, e);
+                Outbox::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Outbox::default(),
+            Err(e) => { info!("Could not read {}: {}", path, e); Outbox::default() }
+        };
+        outbox.path = Some(path.to_string());
+        outbox
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Anything from this scheduler still waiting - later writes have to queue up behind it
+    pub fn is_waiting(&self, scheduler_id: &str) -> bool {
+        self.writes.iter().any(|q| q.write.scheduler() == scheduler_id)
+    }
+
+    pub fn push(&mut self, write: PendingWrite) {
+        self.writes.push(Queued { id: self.next_id, write });
+        self.next_id += 1;
+        self.save();
+    }
+
+    /// Everything waiting, oldest first, leaving the outbox empty until the leftovers are put back
+    pub fn take(&mut self) -> Vec<Queued> {
+        std::mem::take(&mut self.writes)
+    }
+
+    pub fn put_back(&mut self, writes: Vec<Queued>) {
+        self.writes = writes;
+        self.save();
+    }
+
+    /// Changes to the stand-in for a pending add are made to the add itself: closing it means
+    /// not sending it, and rescheduling it changes what gets sent. False if it isn't a stand-in.
+    pub fn amend(&mut self, change: &PendingWrite) -> bool {
+        let idx = match change.item_id().and_then(|id| self.stand_in_for(id)) {
+            None => return false,
+            Some(idx) => idx
+        };
+
+        match change {
+            PendingWrite::Close { .. } => { self.writes.remove(idx); }
+            PendingWrite::Reschedule { description: new_description, due: new_due, .. } => {
+                if let PendingWrite::Add { description, due, .. } = &mut self.writes[idx].write {
+                    *description = new_description.clone();
+                    *due = *new_due;
+                }
+            }
+            PendingWrite::Add { .. } => return false
+        }
+        self.save();
+        true
+    }
+
+    /// Where the add behind this stand-in is waiting
+    fn stand_in_for(&self, id: &ItemId) -> Option<usize> {
+        let pending_id = id.native.strip_prefix(PENDING_PREFIX)?.parse::<u64>().ok()?;
+        self.writes.iter().position(|q| q.id == pending_id &&
+            matches!(&q.write, PendingWrite::Add { scheduler, .. } if *scheduler == id.scheduler))
+    }
+
+    /// The schedule as it will be once everything waiting goes through
+    pub fn overlay(&self, mut items: Vec<ScheduledItem>) -> Vec<ScheduledItem> {
+        for Queued { id: pending_id, write } in self.writes.iter() {
+            match write {
+                PendingWrite::Add { scheduler, description, due } => {
+                    let mut item = ScheduledItem::new(
+                        ItemId::new(scheduler, &format!("{}{}", PENDING_PREFIX, pending_id)),
+                        ScheduleItemType::Todo,
+                        description.clone(),
+                        *due,
+                        None,
+                        None);
+                    item.all_day = is_all_day(*due);
+                    item.pending = true;
+                    items.push(item);
+                }
+                PendingWrite::Close { id, .. } => items.retain(|i| i.id != *id),
+                PendingWrite::Reschedule { id, description, due } => {
+                    for item in items.iter_mut().filter(|i| i.id == *id) {
+                        item.end_time = item.end_time.map(|end| *due + (end - item.start_time));
+                        item.start_time = *due;
+                        item.undated = false;
+                        item.description = description.clone();
+                        item.pending = true;
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    fn save(&self) {
+        let path = match &self.path {
+            None => return,
+            Some(path) => path
+        };
+
+        let saved = serde_json::to_string(self).map_err(anyhow::Error::from).
+            and_then(|contents| write_atomically(path, &contents));
+        if let Err(e) = saved {
+            info!("Could not save the outbox to {}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn item(native: &str, description: &str) -> ScheduledItem {
+        ScheduledItem::new(ItemId::new("local:home", native), ScheduleItemType::Todo,
+                           description.to_string(), Local::today().and_hms(23, 59, 59), None, None)
+    }
+
+    #[test]
+    fn overlays_pending_writes_on_the_schedule() {
+        let tomorrow = Local::today().and_hms(23, 59, 59) + Duration::days(1);
+        let mut outbox = Outbox::default();
+        outbox.push(PendingWrite::Close { id: ItemId::new("local:home", "1"), description: "Buy milk".to_string() });
+        outbox.push(PendingWrite::Reschedule { id: ItemId::new("local:home", "2"), description: "Call mum".to_string(), due: tomorrow });
+        outbox.push(PendingWrite::Add { scheduler: "local:home".to_string(), description: "Water plants".to_string(), due: tomorrow });
+
+        let items = outbox.overlay(vec![item("1", "Buy milk"), item("2", "Call mum")]);
+        assert_eq!(vec!["Call mum", "Water plants"], items.iter().map(|i| i.description.as_str()).collect::<Vec<_>>());
+        assert!(items.iter().all(|i| i.pending && i.start_time == tomorrow));
+        assert!(items[1].all_day);
+    }
+
+    #[test]
+    fn changes_to_a_stand_in_amend_the_pending_add() {
+        let tomorrow = Local::today().and_hms(23, 59, 59) + Duration::days(1);
+        let mut outbox = Outbox::default();
+        outbox.push(PendingWrite::Close { id: ItemId::new("local:home", "1"), description: "Buy milk".to_string() });
+        outbox.push(PendingWrite::Add { scheduler: "local:home".to_string(), description: "Water plants".to_string(), due: Local::now() });
+        let stand_in = outbox.overlay(vec![]).pop().unwrap();
+
+        assert!(!outbox.amend(&PendingWrite::Close { id: ItemId::new("local:home", "1"), description: "Buy milk".to_string() }));
+        assert!(outbox.amend(&PendingWrite::Reschedule { id: stand_in.id.clone(), description: "Water plants".to_string(), due: tomorrow }));
+        assert_eq!(tomorrow, outbox.overlay(vec![]).pop().unwrap().start_time);
+
+        assert!(outbox.amend(&PendingWrite::Close { id: stand_in.id.clone(), description: "Water plants".to_string() }));
+        assert!(outbox.is_waiting("local:home"));
+        assert_eq!(1, outbox.take().len());
+    }
+
+    #[test]
+    fn stand_ins_keep_their_ids_when_earlier_writes_go_through() {
+        let mut outbox = Outbox::default();
+        outbox.push(PendingWrite::Add { scheduler: "local:home".to_string(), description: "Buy milk".to_string(), due: Local::now() });
+        outbox.push(PendingWrite::Add { scheduler: "local:home".to_string(), description: "Water plants".to_string(), due: Local::now() });
+        let stand_in = outbox.overlay(vec![]).pop().unwrap();
+
+        // The first add is sent, the second one is put back
+        let mut writes = outbox.take();
+        writes.remove(0);
+        outbox.put_back(writes);
+
+        assert_eq!(vec![stand_in.id.clone()], outbox.overlay(vec![]).into_iter().map(|i| i.id).collect::<Vec<_>>());
+        assert!(outbox.amend(&PendingWrite::Close { id: stand_in.id, description: "Water plants".to_string() }));
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn survives_a_round_trip_to_disk() {
+        let mut outbox = Outbox::default();
+        outbox.push(PendingWrite::Add { scheduler: "todoist:work".to_string(), description: "Ship it".to_string(), due: Local::now() });
+        outbox.take();
+        outbox.push(PendingWrite::Close { id: ItemId::new("todoist:work", "42"), description: "Review".to_string() });
+
+        let loaded: Outbox = serde_json::from_str(&serde_json::to_string(&outbox).unwrap()).unwrap();
+        assert_eq!(outbox.writes, loaded.writes);
+        assert_eq!(2, loaded.next_id);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities, is_all_day, end_of_day};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::graph_client::{GraphClient, GraphCredentials, GraphTokenStorage, DEFAULT_GRAPH_URL, DEFAULT_LOGIN_URL};
+use crate::google_scheduler::split_location;
+use crate::commands::UICommand;
+use chrono::{DateTime, Local, Utc, Duration, NaiveDateTime, TimeZone};
+use std::error::Error;
+use std::fs::File;
+use std::ops::Add;
+use std::sync::mpsc::Sender;
+use log::info;
+
+#[derive(Deserialize,Debug,Clone)]
+struct GraphDateTime {
+    #[serde(rename = "dateTime")]
+    date_time: String, // No offset - we ask for everything in UTC
+}
+
+#[derive(Deserialize,Debug,Clone)]
+struct GraphLocation {
+    #[serde(rename = "displayName")]
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize,Debug,Clone)]
+#[serde(rename_all = "camelCase")]
+struct GraphEvent {
+    id: String,
+    subject: Option<String>,
+    start: GraphDateTime,
+    end: Option<GraphDateTime>,
+    location: Option<GraphLocation>,
+    #[serde(default)]
+    is_all_day: bool,
+    #[serde(default)]
+    is_cancelled: bool,
+    body_preview: Option<String>,
+    web_link: Option<String>,
+    importance: Option<String>,
+    #[serde(default)]
+    categories: Vec<String>,
+}
+
+#[derive(Deserialize,Debug)]
+struct GraphCalendar {
+    id: String,
+    name: String,
+}
+
+pub struct OutlookScheduler {
+    name: String,
+    calendar: Option<String>, // None means the user's default calendar
+    calendar_id: Option<String>,
+    client: GraphClient,
+    events: Vec<GraphEvent>,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_outlook_scheduler(name: String, calendar: Option<String>, base_url: Option<String>, login_url: Option<String>, ui_tx: Sender<UICommand>) -> Result<OutlookScheduler, Box<dyn Error>> {
+    let file = File::open(format!("config/{}.json", name))?;
+    let credentials: GraphCredentials = serde_json::from_reader(file).expect("Badly formatted Microsoft Graph credentials file!");
+    let storage = GraphTokenStorage { program_name: name.clone(), db_dir: "config".to_string() };
+    let mut client = GraphClient::new(
+        base_url.as_deref().unwrap_or(DEFAULT_GRAPH_URL),
+        login_url.as_deref().unwrap_or(DEFAULT_LOGIN_URL),
+        credentials, storage, ui_tx.clone())?;
+    // Get the sign-in code in front of the user straight away, rather than on the first refresh.
+    // It carries on in the background, and refreshes say how it's going.
+    if let Err(e) = client.sign_in() {
+        info!("Not signed in to Microsoft for {} yet: {}", name, e);
+    }
+    Ok(OutlookScheduler::new(name, calendar, client, ui_tx))
+}
+
+impl OutlookScheduler {
+    pub fn new(name: String, calendar: Option<String>, client: GraphClient, ui_tx: Sender<UICommand>) -> Self {
+        OutlookScheduler { name, calendar, client, ui_tx, calendar_id: None, events: Vec::new(), cache: Vec::new() }
+    }
+
+    /// "me/calendar" for the default calendar, otherwise look the named one up once
+    fn calendar_path(&mut self) -> SchedulerResult<String> {
+        let name = match &self.calendar {
+            None => return Ok("me/calendar".to_string()),
+            Some(name) => name.clone()
+        };
+
+        if self.calendar_id.is_none() {
+            let calendars: Vec<GraphCalendar> = serde_json::from_value(self.client.get("me/calendars", &[])?["value"].clone())?;
+            match calendars.iter().find(|c| c.name == name) {
+                None => return Err(SchedulerError::NotFound(format!("No Outlook calendar named '{}'", name))),
+                Some(calendar) => self.calendar_id = Some(calendar.id.clone())
+            }
+        }
+
+        Ok(format!("me/calendars/{}", self.calendar_id.as_ref().unwrap()))
+    }
+
+}
+
+impl Scheduler for OutlookScheduler {
+    fn id(&self) -> String {
+        format!("outlook:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: false, delete: true, times: true, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let start_time = to_graph_time(Local::now().add(Duration::minutes(-10)));
+        let end_time = to_graph_time(Local::now().add(Duration::days(2)));
+
+        let path = format!("{}/calendarView", self.calendar_path()?);
+        let mut page = self.client.get(&path, &[("startDateTime", start_time.as_str()), ("endDateTime", end_time.as_str())])?;
+        let mut events: Vec<GraphEvent> = vec![];
+        loop {
+            events.extend(serde_json::from_value::<Vec<GraphEvent>>(page["value"].clone())?);
+            match page["@odata.nextLink"].as_str().map(|s| s.to_string()) {
+                None => break,
+                Some(next) => page = self.client.get_url(&next)?
+            }
+        }
+
+        let scheduler_id = self.id();
+        self.cache = events.iter().
+            filter(|e| !e.is_cancelled).
+            filter_map(|e| event_to_scheduled_item(&scheduler_id, e)).
+            collect();
+        self.events = events;
+
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        info!("Adding '{}' to Outlook calendar {}", description, self.name);
+        let (subject, location) = split_location(description);
+        let start = due_date.unwrap_or(Local::now());
+
+        let mut event = if is_all_day(start) {
+            // All day events run midnight to midnight
+            let day = start.date().naive_local().and_hms(0, 0, 0);
+            serde_json::json!({
+                "isAllDay": true,
+                "start": { "dateTime": day.format("%Y-%m-%dT%H:%M:%S").to_string(), "timeZone": "UTC" },
+                "end": { "dateTime": (day + Duration::days(1)).format("%Y-%m-%dT%H:%M:%S").to_string(), "timeZone": "UTC" },
+            })
+        } else {
+            serde_json::json!({
+                "start": { "dateTime": to_graph_time(start), "timeZone": "UTC" },
+                "end": { "dateTime": to_graph_time(start + Duration::hours(1)), "timeZone": "UTC" },
+            })
+        };
+        event["subject"] = serde_json::json!(subject);
+        if let Some(location) = location {
+            event["location"] = serde_json::json!({ "displayName": location });
+        }
+
+        let path = self.calendar_path()?;
+        self.client.post(&format!("{}/events", path), &event)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let existing = match self.events.iter().find(|e| e.id == *id) {
+            None => return Err(SchedulerError::NotFound(format!("No Outlook event {}", id))),
+            Some(e) => e.clone()
+        };
+
+        let mut patch = serde_json::json!({ "subject": description });
+        if let Some(due_date) = due_date {
+            // Keep the event the same length when it moves
+            let old_start = parse_graph_time(&existing.start.date_time);
+            let old_end = existing.end.as_ref().and_then(|e| parse_graph_time(&e.date_time));
+            let length = match (old_start, old_end) {
+                (Some(start), Some(end)) => end - start,
+                _ => Duration::hours(1)
+            };
+
+            let start = if existing.is_all_day {
+                Utc.from_utc_datetime(&due_date.date().naive_local().and_hms(0, 0, 0)).with_timezone(&Local)
+            } else {
+                due_date
+            };
+            patch["start"] = serde_json::json!({ "dateTime": to_graph_time(start), "timeZone": "UTC" });
+            patch["end"] = serde_json::json!({ "dateTime": to_graph_time(start + length), "timeZone": "UTC" });
+        }
+
+        self.client.patch(&format!("me/events/{}", id), &patch)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Deleting event {} from Outlook calendar {}", id, self.name);
+        self.client.delete(&format!("me/events/{}", id))?;
+        Ok(true)
+    }
+}
+
+fn event_to_scheduled_item(scheduler_id: &String, e: &GraphEvent) -> Option<ScheduledItem> {
+    let (start_time, end_time) = if e.is_all_day {
+        // All day events come back as UTC midnights, but they mean a local day
+        let day = NaiveDateTime::parse_from_str(e.start.date_time.get(..19)?, "%Y-%m-%dT%H:%M:%S").ok()?.date();
+        (end_of_day(day), None)
+    } else {
+        (parse_graph_time(&e.start.date_time)?, e.end.as_ref().and_then(|end| parse_graph_time(&end.date_time)))
+    };
+
+    let mut item = ScheduledItem::new(
+        ItemId::new(scheduler_id, &e.id),
+        ScheduleItemType::Calendar,
+        e.subject.clone().unwrap_or("no desc".to_string()),
+        start_time,
+        end_time,
+        e.location.as_ref().and_then(|l| l.display_name.clone()).filter(|l| !l.is_empty()));
+    item.all_day = e.is_all_day;
+    item.priority = match e.importance.as_deref() {
+        Some("high") => Some(1),
+        _ => None
+    };
+    item.tags = e.categories.clone();
+    item.notes = e.body_preview.clone().filter(|n| !n.is_empty());
+    item.link = e.web_link.clone();
+    Some(item)
+}
+
+fn to_graph_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/// "2026-10-20T17:00:00.0000000", in UTC
+fn parse_graph_time(time: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    Some(Utc.from_utc_datetime(&naive).with_timezone(&Local))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_graph_times_as_utc() {
+        let time = parse_graph_time("2026-10-20T17:00:00.0000000").unwrap();
+        assert_eq!(Utc.ymd(2026, 10, 20).and_hms(17, 0, 0), time.with_timezone(&Utc));
+    }
+
+    #[test]
+    fn skips_all_day_events_with_short_times() {
+        let event: GraphEvent = serde_json::from_str(r#"{
+            "id": "AAMk", "subject": "Holiday", "isAllDay": true,
+            "start": {"dateTime": "2026-10-20", "timeZone": "UTC"}
+        }"#).unwrap();
+
+        assert!(event_to_scheduled_item(&"outlook:work".to_string(), &event).is_none());
+    }
+
+    #[test]
+    fn converts_events() {
+        let event: GraphEvent = serde_json::from_str(r#"{
+            "id": "AAMk", "subject": "Design review", "isAllDay": false, "importance": "high",
+            "start": {"dateTime": "2026-10-20T17:00:00.0000000", "timeZone": "UTC"},
+            "end": {"dateTime": "2026-10-20T18:00:00.0000000", "timeZone": "UTC"},
+            "location": {"displayName": "Room 4"}, "categories": ["Work"]
+        }"#).unwrap();
+
+        let item = event_to_scheduled_item(&"outlook:work".to_string(), &event).unwrap();
+        assert_eq!(ItemId::new("outlook:work", "AAMk"), item.id);
+        assert_eq!(Some("Room 4".to_string()), item.place);
+        assert_eq!(Some(1), item.priority);
+        assert_eq!(Duration::hours(1), item.end_time.unwrap() - item.start_time);
+    }
+}
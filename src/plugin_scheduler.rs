@@ -0,0 +1,417 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::commands::UICommand;
+use crate::display::{PromptMessage, PromptMessageType};
+use chrono::{DateTime, Local};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use log::info;
+
+// Any executable can be a scheduler by speaking JSON over stdio, one message per line.
+//
+// todor writes requests to the plugin's stdin:
+//   {"id": 1, "method": "refresh", "params": {}}
+//   {"id": 2, "method": "add", "params": {"description": "Fix the build", "due": "2026-10-20T23:59:59-07:00"}}
+//   {"id": 3, "method": "update", "params": {"id": "PROJ-123", "description": "Fix the build", "due": "..."}}
+//   {"id": 4, "method": "remove", "params": {"id": "PROJ-123"}}
+//
+// ...and expects exactly one response line on stdout for each, carrying the same id:
+//   {"id": 1, "result": {"items": [{"id": "PROJ-123", "description": "Fix the build", "start_time": "...", "all_day": true}]}}
+//   {"id": 1, "result": {"items": [], "capabilities": {"add": false, "reschedule": false, "complete": false, "delete": false}}}
+//   {"id": 2, "result": {"handled": true}}
+//   {"id": 3, "error": "PROJ-123 is locked"}
+//
+// The refresh result can also say what the plugin supports (see Capabilities) - anything it
+// leaves out is assumed to work, apart from comments, which the protocol has no room for.
+// Anything the plugin prints to stderr goes to todor's log.
+
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+// How often to check the plugin is still alive
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize,Debug)]
+struct PluginRequest {
+    id: u64,
+    method: String,
+    params: serde_json::Value,
+}
+
+#[derive(Deserialize,Debug)]
+struct PluginResponse {
+    id: u64,
+    result: Option<serde_json::Value>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize,Debug)]
+struct RefreshResult {
+    items: Vec<PluginItem>,
+    #[serde(default)]
+    capabilities: serde_json::Map<String, serde_json::Value>, // only what the plugin chose to mention
+}
+
+#[derive(Deserialize,Debug)]
+struct WriteResult {
+    #[serde(default)]
+    handled: bool,
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct PluginItem {
+    pub id: String,
+    pub description: String,
+    #[serde(default = "default_item_type")]
+    pub item_type: ScheduleItemType,
+    pub start_time: DateTime<Local>,
+    pub end_time: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub all_day: bool, // start_time's date is all that counts
+    pub place: Option<String>,
+    pub priority: Option<u8>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub link: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+fn default_item_type() -> ScheduleItemType { ScheduleItemType::Todo }
+
+/// A running plugin, with its stdout read on a background thread so we can time out
+struct PluginProcess {
+    child: Arc<Mutex<Child>>, // shared with the thread watching for it to die
+    stdin: ChildStdin,
+    responses: Receiver<String>,
+    reported: Arc<AtomicBool>, // the user has already heard it's gone
+}
+
+pub struct PluginScheduler {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    process: Option<PluginProcess>,
+    next_request_id: u64,
+    capabilities: Capabilities,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_plugin_scheduler(name: String, command: String, args: Vec<String>, ui_tx: Sender<UICommand>) -> Result<PluginScheduler, Box<dyn Error>> {
+    let mut scheduler = PluginScheduler::new(name, command, args, ui_tx);
+    // Fail early if the plugin can't even start
+    scheduler.process = Some(scheduler.spawn()?);
+    Ok(scheduler)
+}
+
+impl PluginScheduler {
+    pub fn new(name: String, command: String, args: Vec<String>, ui_tx: Sender<UICommand>) -> Self {
+        PluginScheduler { name, command, args, ui_tx, process: None, next_request_id: 0, capabilities: plugin_capabilities(&serde_json::Map::new()), cache: Vec::new() }
+    }
+
+    fn spawn(&self) -> anyhow::Result<PluginProcess> {
+        info!("Starting plugin {}: {} {:?}", self.name, self.command, self.args);
+        let mut child = Command::new(&self.command).
+            args(&self.args).
+            stdin(Stdio::piped()).
+            stdout(Stdio::piped()).
+            stderr(Stdio::piped()).
+            spawn()?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+
+        let (tx, responses) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if tx.send(line).is_err() { break; },
+                    Err(_) => break
+                }
+            }
+        });
+
+        let name = self.name.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                match line {
+                    Ok(line) => info!("[plugin {}] {}", name, line),
+                    Err(_) => break
+                }
+            }
+        });
+
+        let child = Arc::new(Mutex::new(child));
+        let reported = Arc::new(AtomicBool::new(false));
+        self.watch(Arc::downgrade(&child), reported.clone());
+
+        Ok(PluginProcess { child, stdin, responses, reported })
+    }
+
+    /// Tell the user as soon as the plugin dies, rather than on the next request.
+    /// Stops once the plugin has been replaced or shut down.
+    fn watch(&self, child: Weak<Mutex<Child>>, reported: Arc<AtomicBool>) {
+        let name = self.name.clone();
+        let ui_tx = self.ui_tx.clone();
+        thread::spawn(move || {
+            loop {
+                thread::sleep(WATCH_INTERVAL);
+                let child = match child.upgrade() {
+                    None => return,
+                    Some(child) => child
+                };
+                let status = match child.lock().unwrap_or_else(|e| e.into_inner()).try_wait() {
+                    Ok(None) => continue,
+                    Ok(Some(status)) => status.to_string(),
+                    Err(e) => e.to_string()
+                };
+
+                if !reported.swap(true, Ordering::SeqCst) {
+                    let msg = format!("Plugin {} exited ({}) - restarting it on the next refresh", name, status);
+                    info!("{}", msg);
+                    ui_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error))).ok();
+                }
+                return;
+            }
+        });
+    }
+
+    /// Make sure the plugin is still running, restarting it (and telling the user) if it died
+    fn ensure_running(&mut self) -> anyhow::Result<()> {
+        let exited = match self.process.as_mut() {
+            None => None,
+            Some(process) => process.child.lock().unwrap_or_else(|e| e.into_inner()).try_wait()?.map(|status| status.to_string())
+        };
+
+        if let Some(status) = exited {
+            self.crashed(&format!("exited ({})", status));
+        }
+
+        if self.process.is_none() {
+            self.process = Some(self.spawn()?);
+        }
+        Ok(())
+    }
+
+    fn crashed(&mut self, reason: &str) {
+        let msg = format!("Plugin {} {} - restarting it", self.name, reason);
+        info!("{}", msg);
+        // The watcher may have beaten us to it
+        let reported = self.process.as_ref().map_or(false, |p| p.reported.swap(true, Ordering::SeqCst));
+        if !reported {
+            self.ui_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error))).ok();
+        }
+
+        if let Some(process) = self.process.take() {
+            process.stop();
+        }
+    }
+
+    fn request(&mut self, method: &str, params: serde_json::Value) -> SchedulerResult<serde_json::Value> {
+        self.ensure_running()?;
+
+        self.next_request_id += 1;
+        let request = PluginRequest { id: self.next_request_id, method: method.to_string(), params };
+        let line = serde_json::to_string(&request)?;
+
+        let process = self.process.as_mut().unwrap();
+        if let Err(e) = writeln!(process.stdin, "{}", line).and_then(|_| process.stdin.flush()) {
+            self.crashed("stopped reading requests");
+            return Err(SchedulerError::Other(format!("Could not send '{}' to plugin {}: {}", method, self.name, e)));
+        }
+
+        loop {
+            let received = self.process.as_ref().unwrap().responses.recv_timeout(RESPONSE_TIMEOUT);
+            let line = match received {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.crashed(&format!("didn't answer '{}' in time", method));
+                    return Err(SchedulerError::Network(format!("Plugin {} timed out", self.name)));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.crashed("closed its output");
+                    return Err(SchedulerError::Other(format!("Plugin {} exited", self.name)));
+                }
+            };
+
+            match parse_response(&line) {
+                Ok(response) if response.id == request.id => {
+                    return match response.error {
+                        Some(error) => Err(SchedulerError::Other(format!("{}: {}", self.name, error))),
+                        None => Ok(response.result.unwrap_or(serde_json::Value::Null))
+                    };
+                }
+                Ok(response) => info!("Plugin {} answered stale request {}", self.name, response.id),
+                Err(e) => info!("Plugin {} sent something odd ({}): {}", self.name, e, line)
+            }
+        }
+    }
+
+    fn write_request(&mut self, method: &str, params: serde_json::Value) -> SchedulerResult<bool> {
+        let result = self.request(method, params)?;
+        let handled = serde_json::from_value::<WriteResult>(result).map_or(false, |r| r.handled);
+
+        if handled {
+            self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+        }
+
+        Ok(handled)
+    }
+}
+
+impl Drop for PluginScheduler {
+    fn drop(&mut self) {
+        if let Some(process) = self.process.take() {
+            process.stop();
+        }
+    }
+}
+
+impl PluginProcess {
+    /// Shut the plugin down on purpose, without the watcher calling it a crash
+    fn stop(&self) {
+        self.reported.store(true, Ordering::SeqCst);
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        child.kill().ok();
+        child.wait().ok();
+    }
+}
+
+impl Scheduler for PluginScheduler {
+    fn id(&self) -> String {
+        format!("plugin:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities.clone()
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        let result = self.request("refresh", serde_json::json!({}))?;
+        let refreshed: RefreshResult = serde_json::from_value(result)?;
+
+        let scheduler_id = self.id();
+        self.cache = refreshed.items.iter().map(|i| plugin_item_to_scheduled_item(&scheduler_id, i)).collect();
+        self.capabilities = plugin_capabilities(&refreshed.capabilities);
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        info!("Adding '{}' with plugin {}", description, self.name);
+        self.write_request("add", serde_json::json!({ "description": description, "due": due_date }))
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        self.write_request("update", serde_json::json!({ "id": id, "description": description, "due": due_date }))
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Asking plugin {} to remove {}", self.name, id);
+        self.write_request("remove", serde_json::json!({ "id": id }))
+    }
+}
+
+/// What the plugin says it supports, on top of assuming it supports everything
+fn plugin_capabilities(given: &serde_json::Map<String, serde_json::Value>) -> Capabilities {
+    let all = Capabilities { add: true, reschedule: true, complete: true, delete: true, times: true, comments: false };
+    let mut merged = match serde_json::to_value(all.clone()) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return all
+    };
+    for (name, value) in given {
+        // There's no way to ask a plugin for comments, whatever it says
+        if name != "comments" { merged.insert(name.clone(), value.clone()); }
+    }
+    serde_json::from_value(serde_json::Value::Object(merged)).unwrap_or(all)
+}
+
+fn parse_response(line: &str) -> serde_json::Result<PluginResponse> {
+    serde_json::from_str(line)
+}
+
+fn plugin_item_to_scheduled_item(scheduler_id: &String, item: &PluginItem) -> ScheduledItem {
+    let mut scheduled = ScheduledItem::new(
+        ItemId::new(scheduler_id, &item.id),
+        item.item_type.clone(),
+        item.description.clone(),
+        item.start_time,
+        item.end_time,
+        item.place.clone());
+    scheduled.all_day = item.all_day;
+    scheduled.priority = item.priority;
+    scheduled.tags = item.tags.clone();
+    scheduled.notes = item.notes.clone();
+    scheduled.link = item.link.clone();
+    scheduled.parent_id = item.parent_id.as_ref().map(|p| ItemId::new(scheduler_id, p));
+    scheduled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_refresh_results() {
+        let response = parse_response(r#"{"id": 1, "result": {"items": [
+            {"id": "PROJ-123", "description": "Fix the build", "start_time": "2026-10-20T10:00:00Z", "priority": 1, "tags": ["jira"]},
+            {"id": "PROJ-124", "description": "Team sync", "item_type": "Calendar", "start_time": "2026-10-20T11:00:00Z", "end_time": "2026-10-20T11:30:00Z"}
+        ]}}"#).unwrap();
+        let refreshed: RefreshResult = serde_json::from_value(response.result.unwrap()).unwrap();
+
+        let items = refreshed.items.iter().map(|i| plugin_item_to_scheduled_item(&"plugin:jira".to_string(), i)).collect::<Vec<_>>();
+        assert_eq!(ItemId::new("plugin:jira", "PROJ-123"), items[0].id);
+        assert_eq!(Some(1), items[0].priority);
+        assert_eq!(ScheduleItemType::Todo, items[0].item_type);
+        assert_eq!(ScheduleItemType::Calendar, items[1].item_type);
+        assert!(items[1].end_time.is_some());
+        assert_eq!(Capabilities { add: true, reschedule: true, complete: true, delete: true, times: true, comments: false },
+                   plugin_capabilities(&refreshed.capabilities));
+    }
+
+    #[test]
+    fn plugins_can_be_read_only() {
+        let refreshed: RefreshResult = serde_json::from_str(r#"{"items": [], "capabilities": {"add": false, "reschedule": false, "complete": false, "delete": false}}"#).unwrap();
+        let capabilities = plugin_capabilities(&refreshed.capabilities);
+        assert!(capabilities.is_read_only());
+        assert!(capabilities.times);
+    }
+
+    #[test]
+    fn left_out_capabilities_are_assumed_to_work() {
+        let refreshed: RefreshResult = serde_json::from_str(r#"{"items": [], "capabilities": {"complete": false}}"#).unwrap();
+        let capabilities = plugin_capabilities(&refreshed.capabilities);
+        assert!(!capabilities.complete);
+        assert!(capabilities.delete && capabilities.add && capabilities.can_close());
+    }
+
+    #[test]
+    fn tells_the_user_when_a_plugin_dies() {
+        let (ui_tx, ui_rx) = channel();
+        let scheduler = PluginScheduler::new("quitter".to_string(), "true".to_string(), vec![], ui_tx);
+        let process = scheduler.spawn().unwrap();
+
+        match ui_rx.recv_timeout(Duration::from_secs(5)) {
+            Ok(UICommand::Toast(msg)) => assert!(format!("{:?}", msg).contains("quitter exited")),
+            other => panic!("Expected a toast, got {:?}", other)
+        }
+        drop(process);
+    }
+
+    #[test]
+    fn parses_errors() {
+        let response = parse_response(r#"{"id": 3, "error": "PROJ-123 is locked"}"#).unwrap();
+        assert_eq!(3, response.id);
+        assert_eq!(Some("PROJ-123 is locked".to_string()), response.error);
+        assert!(response.result.is_none());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::scheduled_item::ScheduledItem;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use log::info;
+
+pub const CACHE_FILE: &str = "config/schedule_cache.json";
+
+/// The last schedule we managed to load, so there's something to show before the backends answer
+#[derive(Serialize,Deserialize,Debug,Default)]
+pub struct ScheduleCache {
+    pub refreshed_at: HashMap<String, DateTime<Local>>, // by scheduler id
+    pub items: Vec<ScheduledItem>,
+}
+
+impl ScheduleCache {
+    /// Whatever was saved last time. A missing or unreadable cache is just an empty one.
+    pub fn load(path: &str) -> ScheduleCache {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return ScheduleCache::default(),
+            Err(e) => { info!("Could not read {}: {}", path, e); return ScheduleCache::default(); }
+        };
+
+        match ScheduleCache::parse(&contents) {
+            Ok(cache) => cache,
+            Err(e) => { info!("Ignoring unreadable schedule cache {}: {}", path, e); ScheduleCache::default() }
+        }
+    }
+
+    /// Everything in the cache is stale until its scheduler refreshes again
+    fn parse(contents: &str) -> serde_json::Result<ScheduleCache> {
+        let mut cache: ScheduleCache = serde_json::from_str(contents)?;
+        for item in cache.items.iter_mut() {
+            item.stale = true;
+        }
+        Ok(cache)
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        write_atomically(path, &serde_json::to_string(self)?)
+    }
+
+    /// The cached items that came from this scheduler
+    pub fn items_for(&self, scheduler_id: &str) -> Vec<ScheduledItem> {
+        self.items.iter().filter(|i| i.scheduler() == scheduler_id).cloned().collect()
+    }
+}
+
+/// Write then rename, so a crash halfway through doesn't leave half a file behind
+pub fn write_atomically(path: &str, contents: &str) -> anyhow::Result<()> {
+    let partial = format!("{}.partial", path);
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduled_item::{ItemId, ScheduleItemType};
+
+    #[test]
+    fn cached_items_come_back_stale() {
+        let mut cache = ScheduleCache::default();
+        cache.refreshed_at.insert("local:home".to_string(), Local::now());
+        cache.items.push(ScheduledItem::new(ItemId::new("local:home", "1"), ScheduleItemType::Todo,
+                                            "Buy milk".to_string(), Local::now(), None, None));
+
+        let loaded = ScheduleCache::parse(&serde_json::to_string(&cache).unwrap()).unwrap();
+        assert_eq!(1, loaded.items_for("local:home").len());
+        assert!(loaded.items[0].stale);
+        assert!(loaded.items_for("todoist:work").is_empty());
+        assert!(loaded.refreshed_at.contains_key("local:home"));
+    }
+}
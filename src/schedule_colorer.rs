@@ -9,6 +9,13 @@ const TEN_MIN_TIL: i64 = 10 * 60 * 1000;
 const ONE_MINUTE_PAST: i64 = -60 * 1000;
 
 pub fn color_item(item: &ScheduledItem, text: &String) -> String {
+    // Waiting to be sent, so the user can tell it hasn't actually happened yet
+    if item.pending { return text.cyan().italic().to_string(); }
+    // Left over from last time, it might not be true any more
+    if item.stale { return text.dimmed().to_string(); }
+    if item.days_overdue(Local::today()).is_some() { return text.magenta().to_string(); }
+    // All-day and undated items don't "start", so they're never about to
+    if item.all_day || item.undated { return text.clone(); }
     color_item_with_time_remaining(text, time_remaining_til(item))
 }
 
@@ -31,6 +38,7 @@ fn color_item_with_time_remaining(text: &String, time_remaining: i64) -> String
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scheduled_item::{ScheduleItemType, ItemId};
     use chrono::TimeZone;
     fn abs(val: i64) -> i64 {
         if val < 0 { -val }
@@ -73,10 +81,39 @@ mod tests {
         assert_eq!("Hope you're there", colored);
     }
 
+    #[test]
+    fn all_day_items_are_never_starting_soon() {
+        let mut item = item(120);
+        item.all_day = true;
+        assert_eq!("Pay rent", color_item(&item, &"Pay rent".to_string()));
+    }
+
+    #[test]
+    fn overdue_todos_are_magenta() {
+        let mut item = item(-3 * 24 * 60 * 60);
+        item.item_type = ScheduleItemType::Todo;
+        assert_eq!("Pay rent".magenta().to_string(), color_item(&item, &"Pay rent".to_string()));
+    }
+
+    #[test]
+    fn stale_items_are_dimmed() {
+        let mut item = item(120);
+        item.stale = true;
+        assert_eq!("A meeting".dimmed().to_string(), color_item(&item, &"A meeting".to_string()));
+    }
+
+    #[test]
+    fn pending_items_stand_out() {
+        let mut item = item(120);
+        item.pending = true;
+        item.stale = true;
+        assert_eq!("A meeting".cyan().italic().to_string(), color_item(&item, &"A meeting".to_string()));
+    }
+
     fn item(seconds_in_future: i64) -> ScheduledItem {
         let scheduled_time = Local.timestamp_millis_opt(Local::now().timestamp_millis() + seconds_in_future*1000).unwrap();
         let location = Some("location".to_string());
-        ScheduledItem::new("id".to_string(), "A meeting".to_string(), scheduled_time, None,location)
+        ScheduledItem::new(ItemId::new("test", "id"), ScheduleItemType::Calendar, "A meeting".to_string(), scheduled_time, None,location)
     }
 
 }
\ No newline at end of file
@@ -7,8 +7,14 @@ const TEN_MIN_TIL: i64 = 10 * 60 * 1000;
 const NEG_ETERNITY: i64 = -9223372036854775800; // min_val, basically.
 
 pub fn format_item(item: &ScheduledItem, is_selected: bool, max_width: usize) -> Option<String> {
-    let time_remaining = (item.start_time - Local::now()).num_milliseconds();
-    let formatted = format_item_with_time_remaining(item, time_remaining, max_width);
+    let formatted = if let Some(days) = item.days_overdue(Local::today()) {
+        Some(format_overdue(item, days, max_width))
+    } else if item.all_day || item.undated {
+        Some(format_all_day(item))
+    } else {
+        let time_remaining = (item.start_time - Local::now()).num_milliseconds();
+        format_item_with_time_remaining(item, time_remaining, max_width)
+    };
     if is_selected {
         format_selection(formatted, max_width)
     } else {
@@ -31,6 +37,15 @@ fn format_item_with_time_remaining(item: &ScheduledItem, time_remaining: i64, ma
     }
 }
 
+fn format_overdue(item: &ScheduledItem, days: i64, max_width: usize) -> String {
+    format!("{0:<2$}  {1}d late", item.description, days, max_width + 2)
+}
+
+// There's no time worth showing, and the section header already says "all day"
+fn format_all_day(item: &ScheduledItem) -> String {
+    item.description.clone()
+}
+
 fn format_without_location(item: &ScheduledItem, max_width: usize) -> String {
     format!("{0:<2$}  {1}", item.description, format_time(item.start_time), max_width + 2)
 }
@@ -56,6 +71,7 @@ fn format_time(time: DateTime<Local>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scheduled_item::{ScheduleItemType, ItemId};
     use chrono::TimeZone;
 
     #[test]
@@ -82,11 +98,26 @@ mod tests {
         assert_eq!("A meeting              -13:13", formatted_str);
     }
 
+    #[test]
+    fn all_day_items_have_no_time() {
+        let mut all_day = item();
+        all_day.all_day = true;
+        assert_eq!(">  A meeting           ", format_item(&all_day, true, 20).unwrap());
+    }
+
+    #[test]
+    fn overdue_items_show_how_late_they_are() {
+        let mut overdue = item();
+        overdue.item_type = ScheduleItemType::Todo;
+        overdue.start_time = Local::now() - chrono::Duration::days(3);
+        assert_eq!("A meeting               3d late", format_item(&overdue, false, 20).unwrap());
+    }
+
     fn item() -> ScheduledItem {
         let start_time = Local.ymd(2020, 4, 2).and_hms(12, 1, 13);
         let end_time = Local.ymd(2020, 4, 2).and_hms(13, 13, 13);
         let location = Some("location".to_string());
-        ScheduledItem::new("id".to_string(), "A meeting".to_string(), start_time, Some(end_time), location)
+        ScheduledItem::new(ItemId::new("test", "id"), ScheduleItemType::Calendar, "A meeting".to_string(), start_time, Some(end_time), location)
     }
 
 }
\ No newline at end of file
@@ -1,45 +1,176 @@
-use chrono::{DateTime, Local};
+use chrono::{Date, DateTime, Local, NaiveDate, TimeZone, Timelike};
 use std::error::Error;
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
 
-#[derive(Debug,Clone)]
+/// Which scheduler an item belongs to, and what that backend calls it
+#[derive(Debug,Clone,PartialEq,Eq,Hash,Serialize,Deserialize)]
+pub struct ItemId {
+    pub scheduler: String, // Scheduler::id() of the owning scheduler
+    pub native: String     // The backend's own id - a Todoist task id, an iCal UID, a line number...
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub struct ScheduledItem {
-    pub id: String,
-    pub scheduler: String,
+    pub id: ItemId,
     pub item_type: ScheduleItemType,
     pub description: String,
     pub start_time: DateTime<Local>,
     pub end_time: Option<DateTime<Local>>,
-    pub place: Option<String>
+    pub all_day: bool, // Date only - start_time is the end of that day, and means nothing more
+    #[serde(default)]
+    pub undated: bool, // Not due at all - start_time is only a placeholder
+    pub place: Option<String>,
+    pub priority: Option<u8>, // 1 is the most urgent
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub link: Option<String>,
+    pub parent_id: Option<ItemId>, // id of the item this is a subtask of
+    #[serde(skip)]
+    pub stale: bool, // Loaded from the cache, and not yet confirmed by a refresh
+    #[serde(skip)]
+    pub pending: bool // Changed here, waiting in the outbox to reach its scheduler
 }
 
-#[derive(Debug,Clone)]
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
 pub enum ScheduleItemType {
     Todo,
     Calendar
 }
 
+/// What a scheduler can do with its items, so we can say no up front instead of failing halfway
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
+#[serde(default)]
+pub struct Capabilities {
+    pub add: bool,
+    pub reschedule: bool,
+    pub complete: bool, // closing an item marks it done...
+    pub delete: bool,   // ...or deletes it outright
+    pub times: bool,    // false when it only keeps dates
+    pub comments: bool,
+}
+
+#[derive(Debug,Clone)]
+pub struct ItemComment {
+    pub posted_at: Option<DateTime<Local>>,
+    pub content: String
+}
+
+impl ItemId {
+    pub fn new(scheduler: &str, native: &str) -> ItemId {
+        ItemId { scheduler: scheduler.to_string(), native: native.to_string() }
+    }
+}
+
+impl fmt::Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.scheduler, self.native)
+    }
+}
+
 impl ScheduledItem {
-    pub fn new(id: String, scheduler: String, item_type: ScheduleItemType, description: String, start_time: DateTime<Local>, end_time: Option<DateTime<Local>>, place: Option<String>) -> ScheduledItem {
-        ScheduledItem{ id, scheduler, item_type, description, start_time, end_time, place }
+    pub fn new(id: ItemId, item_type: ScheduleItemType, description: String, start_time: DateTime<Local>, end_time: Option<DateTime<Local>>, place: Option<String>) -> ScheduledItem {
+        ScheduledItem{ id, item_type, description, start_time, end_time, all_day: false, undated: false, place, priority: None, tags: vec![], notes: None, link: None, parent_id: None, stale: false, pending: false }
+    }
+
+    /// The Scheduler::id() of the scheduler this item came from
+    pub fn scheduler(&self) -> &String {
+        &self.id.scheduler
+    }
+
+    /// How many days past its due date a todo is, if it's from before `today`.
+    /// Calendar events that have been and gone aren't overdue, just over, and todos that were never due can't be late.
+    pub fn days_overdue(&self, today: Date<Local>) -> Option<i64> {
+        if self.item_type != ScheduleItemType::Todo || self.undated { return None; }
+        let days = (today - self.start_time.date()).num_days();
+        if days > 0 { Some(days) } else { None }
     }
 }
 
-pub trait Scheduler {
+// We use 23:59:59 to mean "sometime that day", so those are all-day items
+pub(crate) fn is_all_day(time: DateTime<Local>) -> bool {
+    time.hour() == 23 && time.minute() == 59 && time.second() == 59
+}
+
+/// When something that started at `start` starts after being rescheduled to `due`. Reschedules
+/// usually only say which day, so a timed item keeps its time of day unless `due` has one of its own.
+pub(crate) fn rescheduled(start: DateTime<Local>, due: DateTime<Local>) -> DateTime<Local> {
+    if !is_all_day(due) { return due; }
+    Local.from_local_datetime(&due.date().naive_local().and_time(start.time())).earliest().unwrap_or(due)
+}
+
+/// The time an all-day item on this date is kept at. Goes through the local time rather than
+/// the date, which doesn't exist in zones that skip midnight for daylight saving.
+pub(crate) fn end_of_day(date: NaiveDate) -> DateTime<Local> {
+    Local.from_local_datetime(&date.and_hms(23, 59, 59)).earliest().unwrap_or(Local::now())
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities { add: true, reschedule: true, complete: true, delete: false, times: true, comments: false }
+    }
+}
+
+impl Capabilities {
+    pub fn read_only() -> Capabilities {
+        Capabilities { add: false, reschedule: false, complete: false, delete: false, times: true, comments: false }
+    }
+
+    pub fn can_close(&self) -> bool {
+        self.complete || self.delete
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        !(self.add || self.reschedule || self.can_close())
+    }
+}
+
+pub trait Scheduler: Send {
     fn id(&self) -> String;
-    fn refresh(&mut self) -> Result<(), Box<dyn Error>>;
+    fn capabilities(&self) -> Capabilities;
+    fn refresh(&mut self) -> SchedulerResult<()>;
     fn schedule(&self) -> Vec<ScheduledItem>;
-    fn add(&mut self, target: &String, due_date: Option<DateTime<Local>>) -> anyhow::Result<bool>;
-    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> anyhow::Result<bool>;
-    fn remove(&mut self, task: &String) -> anyhow::Result<bool>;
+    fn add(&mut self, target: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool>;
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool>;
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool>;
+
+    // Comments are only supported by some backends
+    fn comments(&mut self, _id: &String) -> SchedulerResult<Vec<ItemComment>> {
+        Err(SchedulerError::Other(format!("{} doesn't support comments", self.id())))
+    }
+
+    fn add_comment(&mut self, _id: &String, _content: &String) -> SchedulerResult<bool> {
+        Err(SchedulerError::Other(format!("{} doesn't support comments", self.id())))
+    }
 }
 
 // Configuration loading
 #[derive(Deserialize)]
 pub struct ScheduleConfig {
+    #[serde(default)]
     pub google_cal: Vec<GoogleConfig>,
-    pub todoist: Vec<TodoistConfig>
+    #[serde(default)]
+    pub google_tasks: Vec<GoogleTasksConfig>,
+    #[serde(default)]
+    pub outlook: Vec<OutlookConfig>,
+    #[serde(default)]
+    pub todoist: Vec<TodoistConfig>,
+    #[serde(default)]
+    pub local: Vec<LocalConfig>,
+    #[serde(default)]
+    pub caldav: Vec<CalDavConfig>,
+    #[serde(default)]
+    pub ics: Vec<IcsConfig>,
+    #[serde(default)]
+    pub todotxt: Vec<TodoTxtConfig>,
+    #[serde(default)]
+    pub markdown: Vec<MarkdownConfig>,
+    #[serde(default)]
+    pub org: Vec<OrgConfig>,
+    #[serde(default)]
+    pub plugin: Vec<PluginConfig>
 }
 
 #[derive(Deserialize)]
@@ -48,10 +179,78 @@ pub struct GoogleConfig {
     pub cal_name: String
 }
 
+#[derive(Deserialize)]
+pub struct GoogleTasksConfig {
+    pub name: String,
+    pub list: Option<String> // The task list's title. Defaults to "My Tasks"
+}
+
+#[derive(Deserialize)]
+pub struct OutlookConfig {
+    pub name: String,
+    pub calendar: Option<String>, // Defaults to the user's main calendar
+    pub base_url: Option<String>, // Override to test against a mock Graph server
+    pub login_url: Option<String>
+}
+
 #[derive(Deserialize)]
 pub struct TodoistConfig {
     pub name: String,
-    pub project: String
+    // Pick one of project, projects, all_projects or filter
+    pub project: Option<String>,
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub all_projects: bool,
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub sync: bool // Use the incremental Sync API instead of re-downloading everything
+}
+
+#[derive(Deserialize)]
+pub struct LocalConfig {
+    pub name: String,
+    pub path: String
+}
+
+#[derive(Deserialize)]
+pub struct CalDavConfig {
+    pub name: String,
+    pub url: String
+}
+
+#[derive(Deserialize)]
+pub struct IcsConfig {
+    pub name: String,
+    pub source: String
+}
+
+#[derive(Deserialize)]
+pub struct TodoTxtConfig {
+    pub name: String,
+    pub path: String
+}
+
+#[derive(Deserialize)]
+pub struct MarkdownConfig {
+    pub name: String,
+    pub path: String, // A directory of notes
+    pub inbox: Option<String> // Where new items go, relative to path. Defaults to todor.md
+}
+
+#[derive(Deserialize)]
+pub struct OrgConfig {
+    pub name: String,
+    pub path: String, // A single .org file, or a directory of them
+    pub inbox: Option<String> // Where new headlines go when path is a directory. Defaults to todor.org
+}
+
+#[derive(Deserialize)]
+pub struct PluginConfig {
+    pub name: String,
+    pub command: String, // Any executable that speaks the protocol in plugin_scheduler.rs
+    #[serde(default)]
+    pub args: Vec<String>
 }
 
 pub fn load_scheduler_config() -> Result<ScheduleConfig, Box<dyn Error>> {
@@ -60,4 +259,24 @@ pub fn load_scheduler_config() -> Result<ScheduleConfig, Box<dyn Error>> {
     conf_file.read_to_string(&mut toml_tasks)?;
     let config: ScheduleConfig = toml::from_str(&toml_tasks)?;
     Ok(config)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(item_type: ScheduleItemType, start_time: DateTime<Local>) -> ScheduledItem {
+        ScheduledItem::new(ItemId::new("test", "id"), item_type, "Pay rent".to_string(), start_time, None, None)
+    }
+
+    #[test]
+    fn todos_from_before_today_are_overdue() {
+        let today = Local.ymd(2026, 10, 20);
+        assert_eq!(Some(3), item(ScheduleItemType::Todo, Local.ymd(2026, 10, 17).and_hms(23, 59, 59)).days_overdue(today));
+        assert_eq!(None, item(ScheduleItemType::Todo, Local.ymd(2026, 10, 20).and_hms(9, 0, 0)).days_overdue(today));
+        assert_eq!(None, item(ScheduleItemType::Calendar, Local.ymd(2026, 10, 17).and_hms(9, 0, 0)).days_overdue(today));
+
+        let mut undated = item(ScheduleItemType::Todo, Local.ymd(2026, 10, 17).and_hms(23, 59, 59));
+        undated.undated = true;
+        assert_eq!(None, undated.days_overdue(today));
+    }
+}
@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::SendError;
+use std::time::Duration;
+
+/// Everything that can go wrong talking to a scheduler, sorted by what the user can do about it
+#[derive(Debug)]
+pub enum SchedulerError {
+    AuthExpired(String),
+    NotFound(String),
+    RateLimited(Option<Duration>), // how long the backend asked us to wait, if it said
+    Network(String),
+    ReadOnly(String),
+    Parse(String),
+    Other(String),
+}
+
+pub type SchedulerResult<T> = Result<T, SchedulerError>;
+
+impl SchedulerError {
+    /// Sort an HTTP failure by its status code
+    pub fn from_status(status: u16, message: String) -> SchedulerError {
+        match status {
+            401 | 403 => SchedulerError::AuthExpired(message),
+            404 | 410 => SchedulerError::NotFound(message),
+            429 => SchedulerError::RateLimited(None),
+            500..=599 => SchedulerError::Network(message),
+            _ => SchedulerError::Other(message)
+        }
+    }
+
+    /// Worth trying again later without the user changing anything
+    pub fn is_transient(&self) -> bool {
+        matches!(self, SchedulerError::Network(_) | SchedulerError::RateLimited(_))
+    }
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchedulerError::AuthExpired(msg) => write!(f, "Sign-in expired or was refused ({}) - refresh to sign in again, or remove the saved token in config/ and restart", msg),
+            SchedulerError::NotFound(msg) => write!(f, "{} - it may have changed elsewhere, refresh and try again", msg),
+            SchedulerError::RateLimited(Some(wait)) => write!(f, "Too many requests - backing off for {}s", wait.as_secs()),
+            SchedulerError::RateLimited(None) => write!(f, "Too many requests - backing off for a while"),
+            SchedulerError::Network(msg) => write!(f, "Could not reach the server ({}) - will try again", msg),
+            SchedulerError::ReadOnly(name) => write!(f, "{} is read-only", name),
+            SchedulerError::Parse(msg) => write!(f, "Could not understand the response: {}", msg),
+            SchedulerError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for SchedulerError {}
+
+impl From<anyhow::Error> for SchedulerError {
+    fn from(e: anyhow::Error) -> Self {
+        // Clients raise SchedulerErrors through anyhow, so unwrap those rather than flatten them
+        match e.downcast::<SchedulerError>() {
+            Ok(e) => e,
+            Err(e) => SchedulerError::Other(e.to_string())
+        }
+    }
+}
+
+impl From<Box<dyn Error>> for SchedulerError {
+    fn from(e: Box<dyn Error>) -> Self {
+        SchedulerError::Other(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for SchedulerError {
+    fn from(e: std::io::Error) -> Self {
+        SchedulerError::Other(e.to_string())
+    }
+}
+
+impl<T> From<SendError<T>> for SchedulerError {
+    fn from(_: SendError<T>) -> Self {
+        SchedulerError::Other("The display has gone away".to_string())
+    }
+}
+
+impl From<serde_json::Error> for SchedulerError {
+    fn from(e: serde_json::Error) -> Self {
+        SchedulerError::Parse(e.to_string())
+    }
+}
+
+impl From<hyper::Error> for SchedulerError {
+    fn from(e: hyper::Error) -> Self {
+        SchedulerError::Network(e.to_string())
+    }
+}
+
+impl From<restson::Error> for SchedulerError {
+    fn from(e: restson::Error) -> Self {
+        match e {
+            restson::Error::HttpError(status, msg) => SchedulerError::from_status(status, msg),
+            restson::Error::SerializeParseError(e) => SchedulerError::Parse(e.to_string()),
+            restson::Error::DeserializeParseError(e, _) => SchedulerError::Parse(e.to_string()),
+            e => SchedulerError::Network(e.to_string())
+        }
+    }
+}
+
+impl From<google_calendar3::Error> for SchedulerError {
+    fn from(e: google_calendar3::Error) -> Self {
+        match e {
+            google_calendar3::Error::BadRequest(response) => SchedulerError::from_status(response.error.code, response.error.message),
+            google_calendar3::Error::Failure(response) => SchedulerError::from_status(response.status.to_u16(), response.status.to_string()),
+            google_calendar3::Error::HttpError(e) => SchedulerError::Network(e.to_string()),
+            google_calendar3::Error::MissingToken(e) => SchedulerError::AuthExpired(e.to_string()),
+            google_calendar3::Error::JsonDecodeError(_, e) => SchedulerError::Parse(e.to_string()),
+            e => SchedulerError::Other(e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_http_statuses() {
+        assert!(matches!(SchedulerError::from_status(401, "".to_string()), SchedulerError::AuthExpired(_)));
+        assert!(matches!(SchedulerError::from_status(404, "".to_string()), SchedulerError::NotFound(_)));
+        assert!(SchedulerError::from_status(429, "".to_string()).is_transient());
+        assert!(SchedulerError::from_status(503, "".to_string()).is_transient());
+        assert!(!SchedulerError::from_status(400, "".to_string()).is_transient());
+    }
+
+    #[test]
+    fn keeps_scheduler_errors_raised_through_anyhow() {
+        let e: anyhow::Error = SchedulerError::NotFound("Task 42".to_string()).into();
+        assert!(matches!(SchedulerError::from(e), SchedulerError::NotFound(_)));
+    }
+}
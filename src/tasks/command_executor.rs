@@ -2,13 +2,34 @@ use crate::tasks::CommandExecutor;
 use crate::commands::{ScheduleCommand, UICommand};
 use std::sync::mpsc::Sender;
 use log::info;
-use crate::scheduled_item::ScheduledItem;
+use crate::scheduled_item::{ScheduledItem, Capabilities};
 use crate::display::{PromptMessage, PromptMessageType};
 use std::time::Duration;
+use std::collections::HashMap;
 
 impl CommandExecutor {
     pub fn new(cmd_tx: Sender<ScheduleCommand>, ui_tx: Sender<UICommand>) -> Self {
-        CommandExecutor { cmd_tx, ui_tx }
+        CommandExecutor { cmd_tx, ui_tx, capabilities: HashMap::new() }
+    }
+
+    pub fn set_capabilities(&mut self, schedulers: &Vec<(String, Capabilities)>) {
+        self.capabilities = schedulers.iter().cloned().collect();
+    }
+
+    /// Whether the item's scheduler can do this - telling the user if it can't
+    fn supports(&self, item: &ScheduledItem, action: &str, allowed: fn(&Capabilities) -> bool) -> bool {
+        match self.capabilities.get(item.scheduler()) {
+            Some(capabilities) if !allowed(capabilities) => {
+                let msg = if capabilities.is_read_only() {
+                    format!("{} is read-only", item.scheduler())
+                } else {
+                    format!("{} can't {} items", item.scheduler(), action)
+                };
+                self.ui_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error))).ok();
+                false
+            }
+            _ => true // Haven't heard otherwise, so let the scheduler have a go
+        }
     }
 
     pub fn execute_command(&mut self, command: &String, selected_item: Option<&ScheduledItem>) -> anyhow::Result<()> {
@@ -20,6 +41,13 @@ impl CommandExecutor {
             "" => {}, // User just hit <enter> on an empty string.
             "refresh" => { self.cmd_tx.send(ScheduleCommand::Refresh)?; },
             "add" => {
+                if !self.capabilities.is_empty() && !self.capabilities.values().any(|c| c.add) {
+                    self.ui_tx.send(UICommand::Toast(
+                        PromptMessage::new("None of your schedulers take new items".to_string(),
+                                           Duration::from_secs(10),
+                                           PromptMessageType::Error)))?;
+                    return Ok(());
+                }
                 self.ui_tx.send(UICommand::TransitionPush("schedule_selection".to_string()));
                 self.ui_tx.send(UICommand::AddGetScheduler(remainder));
             }
@@ -31,28 +59,46 @@ impl CommandExecutor {
                                                Duration::from_secs(10),
                                                PromptMessageType::Normal)));
                     }
+                    Some(item) if !self.supports(item, "reschedule", |c| c.reschedule) => {}
                     Some(item) => {
                         self.cmd_tx.send(ScheduleCommand::Reschedule(
-                            item.scheduler.clone(),
-                            (*item).clone(),
+                            item.id.clone(),
+                            item.description.clone(),
                             remainder));
                     }
                 }
             }
+            "comment" | "comments" => {
+                match selected_item {
+                    None => {
+                        self.ui_tx.send(UICommand::Toast(
+                            PromptMessage::new("Select a task first!".to_string(),
+                                               Duration::from_secs(10),
+                                               PromptMessageType::Normal)))?;
+                    }
+                    Some(item) if !self.supports(item, "comment on", |c| c.comments) => {}
+                    Some(item) if remainder.is_empty() => {
+                        // Just looking
+                        self.ui_tx.send(UICommand::TransitionPush("comments".to_string()))?;
+                        self.cmd_tx.send(ScheduleCommand::Comments(item.clone()))?;
+                    }
+                    Some(item) => {
+                        self.cmd_tx.send(ScheduleCommand::AddComment(item.clone(), remainder))?;
+                    }
+                }
+            }
             "close" | "ack" => {
-                let item_to_close = match &selected_item {
-                    None => { remainder }
-                    Some(item) => { item.description.clone() }
-                };
-
-                info!("Attempting to ack item: {:?}", item_to_close);
-
-                let scheduler_id = match selected_item {
-                    None => { "todoist:Inbox".to_string() } // TODO: Prompt for this
-                    Some(item) => { item.scheduler.clone() }
-                };
-
-                self.cmd_tx.send(ScheduleCommand::CloseTodo(scheduler_id, item_to_close))?;
+                match selected_item {
+                    None => {
+                        info!("Attempting to ack item: {:?}", remainder);
+                        self.cmd_tx.send(ScheduleCommand::CloseByDescription(remainder))?;
+                    }
+                    Some(item) if !self.supports(item, "close", |c| c.can_close()) => {}
+                    Some(item) => {
+                        info!("Attempting to ack item: {}", item.id);
+                        self.cmd_tx.send(ScheduleCommand::CloseTodo(item.id.clone()))?;
+                    }
+                }
             }
             _ => { info!("Unknown TodoR command: {}", cmd); }
         }
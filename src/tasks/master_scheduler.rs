@@ -1,8 +1,25 @@
-use crate::scheduled_item::{Scheduler, ScheduleConfig, load_scheduler_config, ScheduledItem};
-use crate::tasks::MasterScheduler;
+use crate::scheduled_item::{Scheduler, ScheduleConfig, load_scheduler_config, ScheduledItem, ItemId, Capabilities};
+use crate::tasks::{MasterScheduler, SchedulerHandle};
 use crate::google_scheduler::create_gcal_scheduler;
-use crate::todoist_scheduler::create_todoist_scheduler;
-use std::sync::mpsc::{Sender, Receiver};
+use crate::google_tasks_scheduler::create_gtasks_scheduler;
+use crate::outlook_scheduler::create_outlook_scheduler;
+use crate::todoist_scheduler::{create_todoist_scheduler, TodoistSelection};
+use crate::local_scheduler::create_local_scheduler;
+use crate::caldav_scheduler::create_caldav_scheduler;
+use crate::ics_scheduler::create_ics_scheduler;
+use crate::todotxt_scheduler::create_todotxt_scheduler;
+use crate::markdown_scheduler::create_markdown_scheduler;
+use crate::org_scheduler::create_org_scheduler;
+use crate::plugin_scheduler::create_plugin_scheduler;
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::schedule_cache::{ScheduleCache, CACHE_FILE};
+use crate::outbox::{Outbox, PendingWrite, OUTBOX_FILE};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::Instant;
 use crate::commands::{UICommand, ScheduleCommand, SchedulerAccountId};
 use std::error::Error;
 use log::info;
@@ -11,24 +28,69 @@ use chrono::{Local, TimeZone, NaiveTime};
 use crate::display::{PromptMessage, PromptMessageType};
 use std::time::Duration;
 
+/// What became of a write
+enum Written {
+    Sent,
+    Queued, // in the outbox until the scheduler is back
+    Failed  // and the user has been told why
+}
+
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(30);
+// How long to leave a rate limited scheduler alone when it doesn't say
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
 impl MasterScheduler {
     pub fn new(ui_sched_tx: Sender<UICommand>, cmd_rx: Receiver<ScheduleCommand>) -> Self {
         let cfg = load_scheduler_config().unwrap();
         let ui_tx = ui_sched_tx.clone();
+        let cache = ScheduleCache::load(CACHE_FILE);
         MasterScheduler {
             cmd_rx,
             ui_sched_tx,
-            schedulers: load_schedulers(cfg, ui_tx).unwrap()
+            schedulers: load_schedulers(cfg, ui_tx).unwrap().into_iter().map(|s| SchedulerHandle::from_cache(s, &cache)).collect(),
+            refresh_timeout: REFRESH_TIMEOUT,
+            cache_path: Some(CACHE_FILE.to_string()),
+            outbox: Outbox::load(OUTBOX_FILE)
         }
     }
 
     pub fn schedulers(&self) -> Vec<String> {
-        self.schedulers.iter().map(|s| s.id().clone()).collect()
+        self.schedulers.iter().map(|s| s.id.clone()).collect()
+    }
+
+    fn scheduler_capabilities(&self) -> Vec<(SchedulerAccountId, Capabilities)> {
+        self.schedulers.iter().map(|s| (s.id.clone(), s.capabilities.clone())).collect()
+    }
+
+    /// The scheduler with this id, as long as it isn't still busy refreshing. Tells the user when it can't be had.
+    fn lock_scheduler(&self, account_id: &str) -> anyhow::Result<Option<MutexGuard<'_, Box<dyn Scheduler>>>> {
+        let handle = match self.schedulers.iter().find(|s| s.id == account_id) {
+            None => { self.unknown_account(account_id)?; return Ok(None); }
+            Some(handle) => handle
+        };
+
+        match handle.scheduler.try_lock() {
+            Ok(scheduler) => Ok(Some(scheduler)),
+            Err(TryLockError::Poisoned(e)) => Ok(Some(e.into_inner())),
+            Err(TryLockError::WouldBlock) => {
+                let msg = format!("{} is still refreshing - try again in a moment", account_id);
+                self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error)))?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn unknown_account(&self, account_id: &str) -> anyhow::Result<()> {
+        let msg = format!("Could not find account '{}'. Schedulers: {:?}", account_id, self.schedulers());
+        self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error)))?;
+        Ok(())
     }
 
     pub fn run(&mut self) -> anyhow::Result<()>{
         // Tell the rest of the system about our schedulers
-        self.ui_sched_tx.send(UICommand::Schedulers(self.schedulers()))?;
+        self.ui_sched_tx.send(UICommand::Schedulers(self.scheduler_capabilities()))?;
+        // Show what we had last time while the backends catch up
+        self.send_schedule()?;
         self.refresh()?;
 
         loop {
@@ -39,30 +101,21 @@ impl MasterScheduler {
                         ScheduleCommand::Add(account_id, task) => {
                             self.add_task(account_id, &task)?;
                         }
-                        ScheduleCommand::Reschedule(account_id, task, reschedule_time) => {
-                            self.reschedule_task(account_id, &task, &reschedule_time);
+                        ScheduleCommand::Reschedule(item_id, description, reschedule_time) => {
+                            self.reschedule_task(&item_id, &description, &reschedule_time)?;
                             self.refresh()?;
                         }
-                        ScheduleCommand::CloseTodo(account_id, task) => {
-
-                            match self.schedulers.iter_mut().find(|f| f.id() == account_id) {
-                                None => {
-                                    let msg = format!("Could not find scheduler {} in {:?}",
-                                                      account_id,
-                                                      self.schedulers.iter().map(|s| s.id()).collect::<Vec<_>>());
-                                    info!("{}", msg);
-                                    self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error)))?;
-                                }
-                                Some(scheduler) => {
-                                    info!("Removing task {}", task);
-                                    if let Ok(true) = scheduler.remove(&task) {
-                                        self.refresh()?;
-                                        self.ui_sched_tx.send(UICommand::ClearSelection)?;
-                                    } else {
-                                        // TODO: Display an error message
-                                    }
-                                }
-                            }
+                        ScheduleCommand::Comments(task) => {
+                            self.show_comments(&task)?;
+                        }
+                        ScheduleCommand::AddComment(task, comment) => {
+                            self.add_comment(&task, &comment)?;
+                        }
+                        ScheduleCommand::CloseTodo(item_id) => {
+                            self.close_task(&item_id)?;
+                        }
+                        ScheduleCommand::CloseByDescription(description) => {
+                            self.close_by_description(&description)?;
                         }
                     }
                 }
@@ -84,51 +137,303 @@ impl MasterScheduler {
 
     fn add_task(&mut self, account_id: SchedulerAccountId, task: &String) -> anyhow::Result<()> {
         info!("Attempting to add '{}' to scheduler '{}' ", task, account_id);
-        match self.schedulers.iter_mut().find(|f| f.id() == account_id) {
-            None => {
-                let msg = format!("Could not find account '{}'. Schedulers: {:?}", account_id, self.schedulers.iter().map(|s| s.id()).collect::<Vec<_>>());
+        // TODO: Replace this with the 'to_event' parser... as soon as I understand how to get data OUT of it.
+        let due_date = match DateParser::parse(&task) {
+            None => { info!("No datetime found in '{}' using today", task); Local::today().and_hms(23, 59, 59) }
+            Some(d) => { info!("Found date {} in '{}'", d, task); Local.from_local_date(&d).and_time(NaiveTime::from_hms(23, 59, 59)).unwrap() }
+        };
+
+        self.write(PendingWrite::Add { scheduler: account_id, description: task.clone(), due: due_date })?;
+        Ok(())
+    }
+
+    fn close_by_description(&mut self, description: &String) -> anyhow::Result<()> {
+        let matches = find_by_description(&self.schedulers, description);
+        match matches.len() {
+            0 => {
+                let msg = format!("Nothing matches '{}'", description);
                 self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error)))?;
             }
-            Some(scheduler) => {
-                // TODO: Replace this with the 'to_event' parser... as soon as I understand how to get data OUT of it.
-                let due_date = match DateParser::parse(&task) {
-                    None => { info!("No datetime found in '{}' using today", task); Local::today().and_hms(23, 59, 59) }
-                    Some(d) => { info!("Found date {} in '{}'", d, task); Local.from_local_date(&d).and_time(NaiveTime::from_hms(23, 59, 59)).unwrap() }
-                };
-                scheduler.add(task, Some(due_date))?;
+            1 => { self.close_task(&matches[0].id)?; }
+            _ => {
+                info!("{} items match '{}', asking which one", matches.len(), description);
+                self.ui_sched_tx.send(UICommand::TransitionPush("item_selection".to_string()))?;
+                self.ui_sched_tx.send(UICommand::Disambiguate("close".to_string(), matches))?;
             }
         }
         Ok(())
     }
 
-    fn reschedule_task(&mut self, account_id: SchedulerAccountId, task: &ScheduledItem, reschedule_time: &String) -> anyhow::Result<()>{
-        info!("Attempting to reschedule '{}' with scheduler '{}' ", task.description, account_id);
-        match self.schedulers.iter_mut().find(|f| f.id() == account_id) {
-            None => {
-                let msg = format!("Could not find account '{}'. Schedulers: {:?}", account_id, self.schedulers.iter().map(|s| s.id()).collect::<Vec<_>>());
-                self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error)))?;
+    fn close_task(&mut self, item_id: &ItemId) -> anyhow::Result<()> {
+        let description = self.schedulers.iter().
+            flat_map(|s| s.last_good.iter()).
+            find(|i| i.id == *item_id).
+            map_or(item_id.native.clone(), |i| i.description.clone());
+
+        info!("Removing task {}", item_id);
+        match self.write(PendingWrite::Close { id: item_id.clone(), description })? {
+            Written::Sent => {
+                self.refresh()?;
+                self.ui_sched_tx.send(UICommand::ClearSelection)?;
             }
-            Some(scheduler) => {
-                let due_date = match DateParser::parse(reschedule_time) {
-                    None => { info!("No datetime found in '{}' using today", reschedule_time); Local::today().and_hms(23, 59, 59) }
-                    Some(d) => { info!("Found date {} in '{}'", d, reschedule_time); Local.from_local_date(&d).and_time(NaiveTime::from_hms(23, 59, 59)).unwrap() }
-                };
+            Written::Queued => { self.ui_sched_tx.send(UICommand::ClearSelection)?; }
+            Written::Failed => {}
+        }
+        Ok(())
+    }
+
+    fn reschedule_task(&mut self, item_id: &ItemId, description: &String, reschedule_time: &String) -> anyhow::Result<()>{
+        info!("Attempting to reschedule '{}' ({})", description, item_id);
+        let due_date = match DateParser::parse(reschedule_time) {
+            None => { info!("No datetime found in '{}' using today", reschedule_time); Local::today().and_hms(23, 59, 59) }
+            Some(d) => { info!("Found date {} in '{}'", d, reschedule_time); Local.from_local_date(&d).and_time(NaiveTime::from_hms(23, 59, 59)).unwrap() }
+        };
+
+        self.write(PendingWrite::Reschedule { id: item_id.clone(), description: description.clone(), due: due_date })?;
+        Ok(())
+    }
+
+    /// Send a change to its scheduler, or queue it in the outbox if the scheduler can't be reached
+    fn write(&mut self, write: PendingWrite) -> anyhow::Result<Written> {
+        let account_id = write.scheduler().to_string();
+        if self.outbox.amend(&write) {
+            info!("Changed the queued add instead: {} {}", write, account_id);
+            self.send_schedule()?;
+            return Ok(Written::Queued);
+        }
+        // Later writes wait behind earlier ones, so they land in the order they were made
+        if self.outbox.is_waiting(&account_id) || self.backing_off(&account_id) {
+            self.queue(write, None)?;
+            return Ok(Written::Queued);
+        }
+
+        let scheduler = match self.schedulers.iter().find(|s| s.id == account_id) {
+            None => { self.unknown_account(&account_id)?; return Ok(Written::Failed); }
+            Some(handle) => handle.scheduler.clone()
+        };
+        let result = match scheduler.try_lock() {
+            Ok(mut scheduler) => apply(&mut scheduler, &write),
+            Err(TryLockError::Poisoned(e)) => apply(&mut e.into_inner(), &write),
+            // Still refreshing - it can have the write as soon as that's done
+            Err(TryLockError::WouldBlock) => {
+                self.queue(write, None)?;
+                return Ok(Written::Queued);
+            }
+        };
 
-                scheduler.update(&task.id.split(":").last().unwrap().to_string(), &task.description, Some(due_date))?;
+        match result {
+            Ok(()) => Ok(Written::Sent),
+            Err(e) if e.is_transient() => {
+                self.back_off(&account_id, &e);
+                self.queue(write, Some(e))?;
+                Ok(Written::Queued)
+            }
+            Err(e) => {
+                // Failures go to the user rather than taking the whole loop down
+                self.scheduler_failed(&account_id, &write.to_string(), e)?;
+                Ok(Written::Failed)
             }
         }
+    }
+
+    fn queue(&mut self, write: PendingWrite, reason: Option<SchedulerError>) -> anyhow::Result<()> {
+        let msg = match reason {
+            Some(e) => format!("Will {} {} once it's back: {}", write, write.scheduler(), e),
+            None => format!("Will {} {} once it's back", write, write.scheduler())
+        };
+        info!("{}", msg);
+        self.outbox.push(write);
+        self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Normal)))?;
+        // Show it as if it had happened, marked as pending
+        self.send_schedule()?;
         Ok(())
     }
 
+    /// Send whatever's waiting for schedulers that just refreshed, oldest first.
+    /// A scheduler that drops out again keeps the rest of its writes for next time.
+    fn replay_outbox(&mut self, reachable: &HashSet<String>) -> anyhow::Result<()> {
+        if self.outbox.is_empty() { return Ok(()); }
+
+        let mut kept = vec![];
+        let mut blocked: HashSet<String> = HashSet::new();
+        let mut replayed = false;
+        for queued in self.outbox.take() {
+            let write = &queued.write;
+            let account_id = write.scheduler().to_string();
+            if blocked.contains(&account_id) || !reachable.contains(&account_id) {
+                kept.push(queued);
+                continue;
+            }
+
+            match self.replay(write) {
+                Ok(()) => {
+                    info!("Replayed: {} {}", write, account_id);
+                    replayed = true;
+                }
+                Err(e) if e.is_transient() => {
+                    self.back_off(&account_id, &e);
+                    blocked.insert(account_id);
+                    kept.push(queued);
+                }
+                Err(e) => {
+                    // Something changed it while we were away - tell the user rather than guess
+                    let msg = format!("Gave up trying to {} {}: {}", write, account_id, e);
+                    info!("{}", msg);
+                    self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), PromptMessageType::Error)))?;
+                }
+            }
+        }
+        self.outbox.put_back(kept);
+
+        if replayed {
+            self.ui_sched_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn replay(&self, write: &PendingWrite) -> SchedulerResult<()> {
+        let handle = match self.schedulers.iter().find(|s| s.id == write.scheduler()) {
+            None => return Err(SchedulerError::NotFound(format!("No scheduler called {}", write.scheduler()))),
+            Some(handle) => handle
+        };
+
+        // The item has to still be there, as far as the refresh we just did can tell
+        if let Some(id) = write.item_id() {
+            if !handle.last_good.iter().any(|i| i.id == *id) {
+                return Err(SchedulerError::NotFound("it was changed or removed elsewhere".to_string()));
+            }
+        }
+
+        match handle.scheduler.try_lock() {
+            Ok(mut scheduler) => apply(&mut scheduler, write),
+            Err(TryLockError::Poisoned(e)) => apply(&mut e.into_inner(), write),
+            Err(TryLockError::WouldBlock) => Err(SchedulerError::Network(format!("{} is busy", write.scheduler())))
+        }
+    }
+
+    fn show_comments(&mut self, task: &ScheduledItem) -> anyhow::Result<()> {
+        let comments = match self.lock_scheduler(task.scheduler())? {
+            None => return Ok(()),
+            Some(mut scheduler) => scheduler.comments(&task.id.native)
+        };
+
+        match comments {
+            Ok(comments) => { self.ui_sched_tx.send(UICommand::Comments(task.clone(), comments))?; }
+            Err(e) => {
+                self.scheduler_failed(task.scheduler(), "load comments from", e)?;
+                self.ui_sched_tx.send(UICommand::Comments(task.clone(), vec![]))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_comment(&mut self, task: &ScheduledItem, comment: &String) -> anyhow::Result<()> {
+        info!("Adding a comment to '{}' ({})", task.description, task.id);
+        let added = match self.lock_scheduler(task.scheduler())? {
+            None => return Ok(()),
+            Some(mut scheduler) => scheduler.add_comment(&task.id.native, comment)
+        };
+
+        match handled(added) {
+            Ok(()) => {
+                self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new("Comment added".to_string(), Duration::from_secs(5), PromptMessageType::Normal)))?;
+                // Keeps the comments window up to date, if it's open
+                self.show_comments(task)?;
+            }
+            Err(e) => self.scheduler_failed(task.scheduler(), "add a comment in", e)?
+        }
+        Ok(())
+    }
+
+    /// Refresh every scheduler at once, giving each of them refresh_timeout to finish.
+    /// Any that fail or run late keep showing what they had last time.
     fn refresh(&mut self) -> anyhow::Result<()>{
-        self.schedulers.
-            iter_mut().
-            for_each(|s| { s.refresh().unwrap(); });
+        let (tx, rx) = channel();
+        let mut pending = HashSet::new();
+        let now = Instant::now();
+        for (idx, handle) in self.schedulers.iter().enumerate() {
+            if handle.retry_after.map_or(false, |t| t > now) {
+                info!("Leaving {} alone until it stops rate limiting us", handle.id);
+                continue;
+            }
+            if let Err(TryLockError::WouldBlock) = handle.scheduler.try_lock() {
+                info!("{} is still busy with its last refresh", handle.id);
+                continue;
+            }
+
+            pending.insert(idx);
+            let scheduler = handle.scheduler.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                // Let go of the scheduler before reporting back, so it's free for the next command
+                let result = {
+                    let mut scheduler = scheduler.lock().unwrap_or_else(|e| e.into_inner());
+                    match panic::catch_unwind(AssertUnwindSafe(|| scheduler.refresh())) {
+                        Ok(Ok(())) => Ok((scheduler.schedule(), scheduler.capabilities())),
+                        Ok(Err(e)) => Err(e),
+                        Err(_) => Err(SchedulerError::Other("it crashed".to_string()))
+                    }
+                };
+                tx.send((idx, result)).ok();
+            });
+        }
+        drop(tx);
 
-        let mut final_schedule = self.schedulers.
+        let deadline = Instant::now() + self.refresh_timeout;
+        let mut capabilities_changed = false;
+        let mut reachable = HashSet::new();
+        while !pending.is_empty() {
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok((idx, result)) => {
+                    pending.remove(&idx);
+                    match result {
+                        Ok((schedule, capabilities)) => {
+                            let handle = &mut self.schedulers[idx];
+                            handle.last_good = schedule;
+                            handle.retry_after = None;
+                            handle.refreshed_at = Some(Local::now());
+                            reachable.insert(handle.id.clone());
+                            capabilities_changed |= handle.capabilities != capabilities;
+                            handle.capabilities = capabilities;
+                        }
+                        Err(e) => {
+                            let id = self.schedulers[idx].id.clone();
+                            self.scheduler_failed(&id, "refresh", e)?
+                        }
+                    }
+                }
+                Err(_) => break // Out of time
+            }
+        }
+        for idx in pending {
+            let id = self.schedulers[idx].id.clone();
+            self.scheduler_failed(&id, "refresh", SchedulerError::Other("it took too long".to_string()))?;
+        }
+
+        // Plugins only tell us what they can do when they refresh
+        if capabilities_changed {
+            self.ui_sched_tx.send(UICommand::Schedulers(self.scheduler_capabilities()))?;
+        }
+
+        self.replay_outbox(&reachable)?;
+
+        self.send_schedule()?;
+        self.save_cache();
+
+        Ok(())
+    }
+
+    /// Everything each scheduler last managed to load, merged
+    fn last_good_schedule(&self) -> Vec<ScheduledItem> {
+        self.schedulers.
             iter().
-            flat_map(|s| s.schedule()).
-            collect::<Vec<_>>();
+            flat_map(|s| s.last_good.clone()).
+            collect()
+    }
+
+    /// What the user sees: the last good schedule, plus whatever's waiting in the outbox
+    fn send_schedule(&self) -> anyhow::Result<()> {
+        let mut final_schedule = self.outbox.overlay(self.last_good_schedule());
 
         final_schedule.sort_by_key(|s| s.start_time);
 
@@ -137,16 +442,333 @@ impl MasterScheduler {
         Ok(())
     }
 
+    fn save_cache(&self) {
+        let path = match &self.cache_path {
+            None => return,
+            Some(path) => path
+        };
+
+        let cache = ScheduleCache {
+            refreshed_at: self.schedulers.iter().filter_map(|s| s.refreshed_at.map(|t| (s.id.clone(), t))).collect(),
+            items: self.last_good_schedule()
+        };
+        // Not worth bothering the user over, we'll just start up slower next time
+        if let Err(e) = cache.save(path) {
+            info!("Could not save the schedule cache to {}: {}", path, e);
+        }
+    }
+
+    /// Leave a scheduler alone for a while if it asked us to
+    fn back_off(&mut self, account_id: &str, e: &SchedulerError) {
+        if let SchedulerError::RateLimited(wait) = e {
+            if let Some(handle) = self.schedulers.iter_mut().find(|s| s.id == account_id) {
+                handle.retry_after = Some(Instant::now() + wait.unwrap_or(RATE_LIMIT_BACKOFF));
+            }
+        }
+    }
+
+    fn backing_off(&self, account_id: &str) -> bool {
+        self.schedulers.iter().
+            find(|s| s.id == account_id).
+            and_then(|s| s.retry_after).
+            map_or(false, |t| t > Instant::now())
+    }
+
+    /// Every scheduler failure ends up here, so they all reach the user the same way
+    fn scheduler_failed(&mut self, account_id: &str, action: &str, e: SchedulerError) -> anyhow::Result<()> {
+        self.back_off(account_id, &e);
+
+        let msg = format!("Could not {} {}: {}", action, account_id, e);
+        info!("{}", msg);
+        // Things that sort themselves out don't need to look as alarming
+        let message_type = if e.is_transient() { PromptMessageType::Normal } else { PromptMessageType::Error };
+        self.ui_sched_tx.send(UICommand::Toast(PromptMessage::new(msg, Duration::from_secs(10), message_type)))?;
+        Ok(())
+    }
+
+}
+
+impl SchedulerHandle {
+    fn new(scheduler: Box<dyn Scheduler>) -> Self {
+        SchedulerHandle {
+            id: scheduler.id(),
+            capabilities: scheduler.capabilities(),
+            scheduler: Arc::new(Mutex::new(scheduler)),
+            last_good: vec![],
+            retry_after: None,
+            refreshed_at: None
+        }
+    }
+
+    /// Start out with whatever this scheduler had when the cache was last saved
+    fn from_cache(scheduler: Box<dyn Scheduler>, cache: &ScheduleCache) -> Self {
+        let mut handle = SchedulerHandle::new(scheduler);
+        handle.last_good = cache.items_for(&handle.id);
+        handle.refreshed_at = cache.refreshed_at.get(&handle.id).cloned();
+        handle
+    }
+}
+
+fn apply(scheduler: &mut Box<dyn Scheduler>, write: &PendingWrite) -> SchedulerResult<()> {
+    handled(match write {
+        PendingWrite::Add { description, due, .. } => scheduler.add(description, Some(*due)),
+        PendingWrite::Close { id, .. } => scheduler.remove(&id.native),
+        PendingWrite::Reschedule { id, description, due } => scheduler.update(&id.native, description, Some(*due)),
+    })
+}
+
+/// Schedulers answer Ok(false) when they went through with a write but nothing came of it
+fn handled(result: SchedulerResult<bool>) -> SchedulerResult<()> {
+    match result? {
+        true => Ok(()),
+        false => Err(SchedulerError::Other("nothing was changed".to_string()))
+    }
 }
 
 fn load_schedulers(cfg: ScheduleConfig, ui_tx: Sender<UICommand>) -> Result<Vec<Box<dyn Scheduler>>, Box<dyn Error>> {
     let mut schedulers: Vec<Box<dyn Scheduler>> = Vec::new();
     for gc in cfg.google_cal {
         let auth_file = format!("config/{}.json", gc.name);
-        schedulers.push(Box::new(create_gcal_scheduler(auth_file, gc.cal_name)?));
+        schedulers.push(Box::new(create_gcal_scheduler(auth_file, gc.cal_name, ui_tx.clone())?));
+    }
+    for gt in cfg.google_tasks {
+        let auth_file = format!("config/{}.json", gt.name);
+        schedulers.push(Box::new(create_gtasks_scheduler(auth_file, gt.list, ui_tx.clone())?));
+    }
+    for ol in cfg.outlook {
+        schedulers.push(Box::new(create_outlook_scheduler(ol.name, ol.calendar, ol.base_url, ol.login_url, ui_tx.clone())?));
     }
     for td in cfg.todoist {
-        schedulers.push(Box::new(create_todoist_scheduler(td.name, td.project, ui_tx.clone())?));
+        let selection = match (td.filter, td.all_projects) {
+            (Some(query), _) => TodoistSelection::Filter(query),
+            (None, true) => TodoistSelection::AllProjects,
+            (None, false) => {
+                let projects: Vec<String> = td.project.into_iter().chain(td.projects.into_iter()).collect();
+                if projects.is_empty() { return Err(format!("Todoist account '{}' has no project configured", td.name).into()); }
+                TodoistSelection::Projects(projects)
+            }
+        };
+        schedulers.push(Box::new(create_todoist_scheduler(td.name, selection, td.sync, ui_tx.clone())?));
+    }
+    for lc in cfg.local {
+        schedulers.push(Box::new(create_local_scheduler(lc.name, lc.path, ui_tx.clone())?));
+    }
+    for cd in cfg.caldav {
+        schedulers.push(Box::new(create_caldav_scheduler(cd.name, cd.url, ui_tx.clone())?));
+    }
+    for ic in cfg.ics {
+        schedulers.push(Box::new(create_ics_scheduler(ic.name, ic.source)?));
+    }
+    for tt in cfg.todotxt {
+        schedulers.push(Box::new(create_todotxt_scheduler(tt.name, tt.path, ui_tx.clone())?));
+    }
+    for md in cfg.markdown {
+        schedulers.push(Box::new(create_markdown_scheduler(md.name, md.path, md.inbox, ui_tx.clone())?));
+    }
+    for org in cfg.org {
+        schedulers.push(Box::new(create_org_scheduler(org.name, org.path, org.inbox, ui_tx.clone())?));
+    }
+    for plugin in cfg.plugin {
+        schedulers.push(Box::new(create_plugin_scheduler(plugin.name, plugin.command, plugin.args, ui_tx.clone())?));
     }
     Ok(schedulers)
-}
\ No newline at end of file
+}
+
+/// Items the user could have meant by typing `description` at the prompt, from schedulers that can close them
+fn find_by_description(schedulers: &[SchedulerHandle], description: &str) -> Vec<ScheduledItem> {
+    schedulers.iter().
+        filter(|s| s.capabilities.can_close()).
+        flat_map(|s| s.last_good.clone()).
+        filter(|i| matches_description(i, description)).
+        collect()
+}
+
+fn matches_description(item: &ScheduledItem, description: &str) -> bool {
+    item.description.trim().eq_ignore_ascii_case(description.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduled_item::ScheduleItemType;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct FakeScheduler {
+        name: String,
+        failing: Arc<AtomicBool>,
+        delay: Duration,
+        cache: Vec<ScheduledItem>,
+        added: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Scheduler for FakeScheduler {
+        fn id(&self) -> String { format!("fake:{}", self.name) }
+
+        fn refresh(&mut self) -> SchedulerResult<()> {
+            thread::sleep(self.delay);
+            if self.failing.load(Ordering::SeqCst) { return Err(SchedulerError::RateLimited(None)); }
+            self.cache = vec![ScheduledItem::new(ItemId::new(&self.id(), "1"), ScheduleItemType::Todo,
+                                                 self.name.clone(), Local::now(), None, None)];
+            Ok(())
+        }
+
+        fn capabilities(&self) -> Capabilities { Capabilities::default() }
+        fn schedule(&self) -> Vec<ScheduledItem> { self.cache.clone() }
+        fn add(&mut self, description: &String, _: Option<chrono::DateTime<Local>>) -> SchedulerResult<bool> {
+            if self.failing.load(Ordering::SeqCst) { return Err(SchedulerError::Network("offline".to_string())); }
+            self.added.lock().unwrap().push(description.clone());
+            Ok(true)
+        }
+        fn update(&mut self, _: &String, _: &String, _: Option<chrono::DateTime<Local>>) -> SchedulerResult<bool> { Ok(false) }
+        fn remove(&mut self, _: &String) -> SchedulerResult<bool> { Err(SchedulerError::NotFound("Item 1".to_string())) }
+    }
+
+    fn fake(name: &str, failing: Arc<AtomicBool>, delay: Duration) -> SchedulerHandle {
+        fake_with(name, failing, delay, Arc::new(Mutex::new(vec![])))
+    }
+
+    fn fake_with(name: &str, failing: Arc<AtomicBool>, delay: Duration, added: Arc<Mutex<Vec<String>>>) -> SchedulerHandle {
+        SchedulerHandle::new(Box::new(FakeScheduler { name: name.to_string(), failing, delay, cache: vec![], added }))
+    }
+
+    fn master(schedulers: Vec<SchedulerHandle>) -> (MasterScheduler, Receiver<UICommand>) {
+        let (ui_tx, ui_rx) = channel();
+        let (_, cmd_rx) = channel();
+        (MasterScheduler { ui_sched_tx: ui_tx, schedulers, cmd_rx, refresh_timeout: Duration::from_millis(200), cache_path: None, outbox: Outbox::default() }, ui_rx)
+    }
+
+    fn scheduled_descriptions(ui_rx: &Receiver<UICommand>) -> (Vec<String>, usize) {
+        let mut toasts = 0;
+        for cmd in ui_rx.try_iter() {
+            match cmd {
+                UICommand::Toast(_) => toasts += 1,
+                UICommand::Schedules(items) => return (items.iter().map(|i| i.description.clone()).collect(), toasts),
+                _ => {}
+            }
+        }
+        panic!("No schedule was sent")
+    }
+
+    #[test]
+    fn failed_refreshes_keep_the_last_good_schedule() {
+        let failing = Arc::new(AtomicBool::new(false));
+        let (mut master, ui_rx) = master(vec![fake("home", failing.clone(), Duration::from_millis(0))]);
+
+        master.refresh().unwrap();
+        assert_eq!((vec!["home".to_string()], 0), scheduled_descriptions(&ui_rx));
+
+        failing.store(true, Ordering::SeqCst);
+        master.refresh().unwrap();
+        assert_eq!((vec!["home".to_string()], 1), scheduled_descriptions(&ui_rx));
+    }
+
+    #[test]
+    fn rate_limited_schedulers_are_left_alone_for_a_while() {
+        let failing = Arc::new(AtomicBool::new(true));
+        let (mut master, ui_rx) = master(vec![fake("home", failing.clone(), Duration::from_millis(0))]);
+
+        master.refresh().unwrap();
+        assert_eq!((vec![], 1), scheduled_descriptions(&ui_rx));
+
+        // Backing off, so no second complaint
+        master.refresh().unwrap();
+        assert_eq!((vec![], 0), scheduled_descriptions(&ui_rx));
+    }
+
+    #[test]
+    fn failed_writes_are_reported() {
+        let (mut master, ui_rx) = master(vec![fake("home", Arc::new(AtomicBool::new(false)), Duration::from_millis(0))]);
+
+        master.close_task(&ItemId::new("fake:home", "1")).unwrap();
+        let toasts: Vec<String> = ui_rx.try_iter().filter_map(|cmd| match cmd {
+            UICommand::Toast(msg) => Some(format!("{:?}", msg)),
+            _ => None
+        }).collect();
+        assert_eq!(1, toasts.len());
+        assert!(toasts[0].contains("changed elsewhere"), "{}", toasts[0]);
+    }
+
+    #[test]
+    fn slow_schedulers_do_not_hold_up_the_rest() {
+        let (mut master, ui_rx) = master(vec![
+            fake("slow", Arc::new(AtomicBool::new(false)), Duration::from_secs(5)),
+            fake("fast", Arc::new(AtomicBool::new(false)), Duration::from_millis(0)),
+        ]);
+
+        let started = Instant::now();
+        master.refresh().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!((vec!["fast".to_string()], 1), scheduled_descriptions(&ui_rx));
+    }
+
+    #[test]
+    fn offline_writes_wait_in_the_outbox_and_replay_in_order() {
+        let failing = Arc::new(AtomicBool::new(true));
+        let added = Arc::new(Mutex::new(vec![]));
+        let (mut master, ui_rx) = master(vec![fake_with("home", failing.clone(), Duration::from_millis(0), added.clone())]);
+
+        master.add_task("fake:home".to_string(), &"Buy milk".to_string()).unwrap();
+        assert_eq!((vec!["Buy milk".to_string()], 1), scheduled_descriptions(&ui_rx));
+
+        // Back online, but the second add still has to wait its turn
+        failing.store(false, Ordering::SeqCst);
+        master.add_task("fake:home".to_string(), &"Water plants".to_string()).unwrap();
+        assert!(added.lock().unwrap().is_empty());
+
+        master.refresh().unwrap();
+        assert_eq!(vec!["Buy milk".to_string(), "Water plants".to_string()], *added.lock().unwrap());
+        assert!(master.outbox.is_empty());
+    }
+
+    #[test]
+    fn writes_to_a_busy_scheduler_wait_in_the_outbox() {
+        let added = Arc::new(Mutex::new(vec![]));
+        let (mut master, ui_rx) = master(vec![fake_with("home", Arc::new(AtomicBool::new(false)), Duration::from_millis(0), added.clone())]);
+
+        let scheduler = master.schedulers[0].scheduler.clone();
+        let refreshing = scheduler.lock().unwrap();
+        master.add_task("fake:home".to_string(), &"Buy milk".to_string()).unwrap();
+        assert_eq!((vec!["Buy milk".to_string()], 1), scheduled_descriptions(&ui_rx));
+        drop(refreshing);
+
+        master.refresh().unwrap();
+        assert_eq!(vec!["Buy milk".to_string()], *added.lock().unwrap());
+        assert!(master.outbox.is_empty());
+    }
+
+    #[test]
+    fn rescheduling_a_pending_add_changes_what_gets_sent() {
+        let failing = Arc::new(AtomicBool::new(true));
+        let added = Arc::new(Mutex::new(vec![]));
+        let (mut master, ui_rx) = master(vec![fake_with("home", failing.clone(), Duration::from_millis(0), added.clone())]);
+
+        master.add_task("fake:home".to_string(), &"Buy milk".to_string()).unwrap();
+        assert_eq!((vec!["Buy milk".to_string()], 1), scheduled_descriptions(&ui_rx));
+        let stand_in = master.outbox.overlay(vec![]).pop().unwrap();
+        master.reschedule_task(&stand_in.id, &"Buy oat milk".to_string(), &"tomorrow".to_string()).unwrap();
+        assert_eq!((vec!["Buy oat milk".to_string()], 0), scheduled_descriptions(&ui_rx));
+
+        failing.store(false, Ordering::SeqCst);
+        master.refresh().unwrap();
+        assert_eq!(vec!["Buy oat milk".to_string()], *added.lock().unwrap());
+        assert!(master.outbox.is_empty());
+    }
+
+    #[test]
+    fn queued_writes_to_items_that_went_away_are_reported() {
+        let (mut master, ui_rx) = master(vec![fake("home", Arc::new(AtomicBool::new(false)), Duration::from_millis(0))]);
+        master.outbox.push(PendingWrite::Close { id: ItemId::new("fake:home", "2"), description: "Gone".to_string() });
+
+        master.refresh().unwrap();
+        assert_eq!((vec!["home".to_string()], 1), scheduled_descriptions(&ui_rx));
+        assert!(master.outbox.is_empty());
+    }
+
+    #[test]
+    fn typed_descriptions_ignore_case_and_spacing() {
+        let item = ScheduledItem::new(ItemId::new("local:home", "1"), ScheduleItemType::Todo,
+                                      "Buy milk".to_string(), Local::now(), None, None);
+        assert!(matches_description(&item, " buy MILK "));
+        assert!(!matches_description(&item, "buy"));
+    }
+}
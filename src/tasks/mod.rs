@@ -1,6 +1,11 @@
+use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{Sender, Receiver};
+use std::time::{Duration, Instant};
 use crate::commands::{UICommand, ScheduleCommand};
-use crate::scheduled_item::Scheduler;
+use crate::scheduled_item::{Scheduler, ScheduledItem, Capabilities};
+use std::collections::HashMap;
+use chrono::{DateTime, Local};
+use crate::outbox::Outbox;
 
 mod master_scheduler;
 mod user_input_task;
@@ -8,8 +13,21 @@ mod command_executor;
 
 pub struct MasterScheduler {
     ui_sched_tx: Sender<UICommand>,
-    schedulers: Vec<Box<dyn Scheduler>>,
+    schedulers: Vec<SchedulerHandle>,
     cmd_rx: Receiver<ScheduleCommand>,
+    refresh_timeout: Duration, // per refresh, for each scheduler
+    cache_path: Option<String>, // where the last good schedule is kept between runs
+    outbox: Outbox, // writes waiting for their scheduler to be reachable again
+}
+
+/// A scheduler that refreshes on a thread of its own, and the last schedule it managed to load
+struct SchedulerHandle {
+    id: String,
+    scheduler: Arc<Mutex<Box<dyn Scheduler>>>,
+    capabilities: Capabilities, // as of the last refresh
+    last_good: Vec<ScheduledItem>,
+    retry_after: Option<Instant>, // set when the backend asks us to slow down
+    refreshed_at: Option<DateTime<Local>>, // when last_good was last confirmed, this run or a previous one
 }
 
 pub struct UserInputTask {
@@ -20,4 +38,5 @@ pub struct UserInputTask {
 pub struct CommandExecutor {
     cmd_tx: Sender<ScheduleCommand>,
     pub ui_tx: Sender<UICommand>,
+    capabilities: HashMap<String, Capabilities>, // by scheduler id
 }
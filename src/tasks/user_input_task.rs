@@ -48,13 +48,11 @@ impl UserInputTask {
             Key::Esc => { self.ui_tx.send(UICommand::ClearSelection)?; }
             Key::Up => { self.ui_tx.send(UICommand::SelectPrev)?; }
             Key::Down => { self.ui_tx.send(UICommand::SelectNext)?; }
+            Key::Right => { self.ui_tx.send(UICommand::ExpandSelection)?; }
+            Key::Left => { self.ui_tx.send(UICommand::CollapseSelection)?; }
 
             // Not implemented below here
             Key::Delete => {/* TODO: Add delete support once we have arrow keys */}
-            // Arrows
-            Key::Left => {}
-            Key::Right => {}
-
             // Scrolling
             Key::Home => {}
             Key::End => {}
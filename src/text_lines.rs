@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Native ids for the lines of a text file: the file, plus a hash of what the line says, so an id
+/// still finds its line after others are added or removed around it. A line repeated in the same
+/// file is told apart by how many times it came before.
+pub(crate) fn line_ids(path: &Path, lines: &[String]) -> Vec<String> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    lines.iter().map(|line| {
+        let repeats = seen.entry(line.as_str()).or_insert(0);
+        let id = match *repeats {
+            0 => format!("{}#{:016x}", path.display(), fnv1a(line)),
+            n => format!("{}#{:016x}.{}", path.display(), fnv1a(line), n)
+        };
+        *repeats += 1;
+        id
+    }).collect()
+}
+
+/// Where the line with this id is now, if it's still there
+pub(crate) fn find_line(id: &str, path: &Path, lines: &[String]) -> Option<usize> {
+    line_ids(path, lines).iter().position(|l| l == id)
+}
+
+/// The file a line id points into
+pub(crate) fn line_path(id: &str) -> Option<&Path> {
+    id.rsplit_once('#').map(|(path, _)| Path::new(path))
+}
+
+/// Write lines back to a file, keeping whichever line ending it already uses
+pub(crate) fn write_lines(path: &Path, lines: &[String]) -> anyhow::Result<()> {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    fs::write(path, join_lines(lines, &existing))?;
+    Ok(())
+}
+
+fn join_lines(lines: &[String], existing: &str) -> String {
+    let newline = if existing.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut contents = lines.join(newline);
+    contents.push_str(newline);
+    contents
+}
+
+// Ids end up in the schedule cache and the outbox, so the hash has to be the same from one run
+// (and one Rust release) to the next, which std's hashers don't promise.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn ids_follow_their_line_around_the_file() {
+        let path = Path::new("notes/today.md");
+        let before = lines(&["- [ ] Buy milk", "- [ ] Call mum", "- [ ] Buy milk"]);
+        let ids = line_ids(path, &before);
+        assert_eq!(Some(path), line_path(&ids[1]));
+        assert_ne!(ids[0], ids[2]);
+
+        let after = lines(&["# Today", "- [ ] Buy milk", "- [ ] Buy milk", "- [ ] Call mum"]);
+        assert_eq!(Some(3), find_line(&ids[1], path, &after));
+        assert_eq!(Some(2), find_line(&ids[2], path, &after));
+        assert_eq!(None, find_line(&ids[1], path, &after[..3]));
+    }
+
+    #[test]
+    fn keeps_the_files_line_endings() {
+        let edited = lines(&["- [x] Buy milk", "- [ ] Call mum"]);
+        assert_eq!("- [x] Buy milk\r\n- [ ] Call mum\r\n", join_lines(&edited, "- [ ] Buy milk\r\n- [ ] Call mum\r\n"));
+        assert_eq!("- [x] Buy milk\n- [ ] Call mum\n", join_lines(&edited, "- [ ] Buy milk\n- [ ] Call mum\n"));
+        assert_eq!("- [x] Buy milk\n- [ ] Call mum\n", join_lines(&edited, ""));
+    }
+}
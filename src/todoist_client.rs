@@ -2,13 +2,14 @@ use restson::{RestPath, Error, RestClient};
 use chrono::{DateTime, Local, SecondsFormat, Utc, TimeZone};
 use log::info;
 
-const URL_BASE: &str = "https://api.todoist.com/";
+pub(crate) const URL_BASE: &str = "https://api.todoist.com/";
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
 pub struct Project {
+    #[serde(default)]
     comment_count: u64,
-    id: String,
-    name: String
+    pub id: String,
+    pub name: String
 }
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
@@ -23,7 +24,12 @@ pub struct Task {
     pub due_date: Option<String>,
     pub due_datetime: Option<String>,
     pub due: Option<TodoistDate>,
-    pub url: String
+    pub url: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    pub parent_id: Option<String>
 }
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
@@ -33,27 +39,61 @@ pub struct NewTask {
     pub due_datetime: Option<String>,
 }
 
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct Comment {
+    pub id: String,
+    pub task_id: Option<String>,
+    pub content: String,
+    pub posted_at: String, // RFC3339, in UTC
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+struct NewComment {
+    task_id: String,
+    content: String,
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+struct Comments ( pub Vec<Comment> ); // alias to help deserialization
+
+/// Only the fields we mean to change - anything else we sent would overwrite what's in Todoist
+#[derive(Serialize,Debug,Clone)]
+pub struct TaskUpdate {
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_datetime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_string: Option<String>,
+}
+
 #[derive(Serialize)]
 struct TaskClose{}
 
-impl Task {
-    pub fn new(pid: String, id: Option<String>, description: String, due: DateTime<Local>) -> Task {
-        Task {
-            id: id.unwrap_or("0".into()),
-            project_id: pid,
-            content: description,
-            is_completed: false,
-            order: None,
-            priority: 0,
-            due_string: None,
-            due_date: None,
-            due_datetime: Some(due.to_rfc3339()),
-            due: None,
-            url: "".to_string()
+impl Project {
+    pub fn new(id: String, name: String) -> Project {
+        Project { comment_count: 0, id, name }
+    }
+}
+
+impl TaskUpdate {
+    pub fn new(description: String, due: DateTime<Local>, recurrence: Option<String>) -> TaskUpdate {
+        match recurrence {
+            // Setting a plain date would turn a recurring task into a one-off
+            Some(recurrence) => TaskUpdate { content: description, due_datetime: None, due_string: Some(recurrence_starting(&recurrence, due)) },
+            None => TaskUpdate { content: description, due_datetime: Some(due.to_rfc3339()), due_string: None }
         }
     }
 }
 
+/// "every monday" -> "every monday starting 2026-10-20", so the recurrence picks up from the new date
+pub fn recurrence_starting(recurrence: &str, due: DateTime<Local>) -> String {
+    let base = match recurrence.to_lowercase().find(" starting ") {
+        Some(idx) => &recurrence[..idx],
+        None => recurrence
+    };
+    format!("{} starting {}", base.trim(), due.format("%Y-%m-%d"))
+}
+
 impl NewTask {
     pub fn new(pid: String, description: String, due: DateTime<Local>) -> NewTask {
         NewTask {
@@ -73,14 +113,20 @@ pub struct TodoistDate {
                                   // definition either in tzdata-compatible format (“Europe/Berlin”)
                                   // or as a string specifying east of UTC offset as “UTC±HH:MM”
                                   // (i.e. “UTC-01:00”).
+    #[serde(default)]
+    pub is_recurring: bool,       // `string` holds the recurrence, e.g. "every monday"
 }
 
-pub trait TodoistClient {
+pub trait TodoistClient: Send {
     fn projects(&self) -> Result<Vec<Project>, Error>;
     fn tasks(&self, project: &str) -> Result<Vec<Task>, Error>;
+    fn all_tasks(&self) -> Result<Vec<Task>, Error>;
+    fn filter(&self, query: &str) -> Result<Vec<Task>, Error>;
     fn add(&self, project: &str, task: String, due_date: Option<DateTime<Local>>) -> Result<bool, Error>;
-    fn reschedule(&self, project: &str, task_id: &str, content: String, due_date: Option<DateTime<Local>>) -> Result<bool, Error>;
+    fn reschedule(&self, project: &str, task_id: &str, content: String, due_date: Option<DateTime<Local>>, recurrence: Option<String>) -> Result<bool, Error>;
     fn close(&self, task_id: &str) ->  Result<bool, Error>;
+    fn comments(&self, task_id: &str) -> Result<Vec<Comment>, Error>;
+    fn add_comment(&self, task_id: &str, content: String) -> Result<bool, Error>;
 }
 
 pub struct TodoistRestClient {
@@ -117,6 +163,20 @@ impl RestPath<&str> for Task {
     }
 }
 
+impl RestPath<&str> for TaskUpdate {
+    fn get_path(task_id: &str) -> Result<String,Error> {
+        Ok(format!("rest/v2/tasks/{}", task_id))
+    }
+}
+
+impl RestPath<()> for Comments {
+    fn get_path(_: ()) -> Result<String,Error> { Ok("rest/v2/comments".to_string()) }
+}
+
+impl RestPath<()> for NewComment {
+    fn get_path(_: ()) -> Result<String,Error> { Ok("rest/v2/comments".to_string()) }
+}
+
 impl RestPath<&str> for TaskClose {
     fn get_path(task_id: &str) -> Result<String,Error> { Ok(format!("rest/v2/tasks/{}/close", task_id)) }
 }
@@ -131,6 +191,15 @@ impl TodoistRestClient {
         client.set_header("Authorization", format!("Bearer {}", self.token).as_str())?;
         Ok(client)
     }
+
+    fn project_id(&self, project: &str) -> Result<String, Error> {
+        find_project(&self.projects()?, project).map(|p| p.id.clone())
+    }
+}
+
+pub(crate) fn find_project<'a>(projects: &'a Vec<Project>, project: &str) -> Result<&'a Project, Error> {
+    projects.iter().find(|p| p.name == project).
+        ok_or(Error::HttpError(404, format!("No project named {}", project)))
 }
 impl TodoistClient for TodoistRestClient {
     fn projects(&self) -> Result<Vec<Project>, Error> {
@@ -142,36 +211,39 @@ impl TodoistClient for TodoistRestClient {
 
     fn tasks(&self, project: &str) -> Result<Vec<Task>, Error> {
         let mut client = self.get_client()?;
-        let projects = self.projects()?;
-        let selected_project = projects.iter().find(|p| p.name == project).
-            expect(format!("No project named {}", project).as_str());
+        let project_id = self.project_id(project)?;
 
-        let tasks: Vec<Task> = client.get_with::<_, Tasks>((), &[("project_id", format!("{}", selected_project.id).as_str())])?.0.iter().
+        let tasks: Vec<Task> = client.get_with::<_, Tasks>((), &[("project_id", project_id.as_str())])?.0.iter().
             map(|t| t.to_owned()).
             collect();
         Ok(tasks)
     }
 
+    fn all_tasks(&self) -> Result<Vec<Task>, Error> {
+        let mut client = self.get_client()?;
+        Ok(client.get::<_, Tasks>(())?.0)
+    }
+
+    fn filter(&self, query: &str) -> Result<Vec<Task>, Error> {
+        let mut client = self.get_client()?;
+        Ok(client.get_with::<_, Tasks>((), &[("filter", query)])?.0)
+    }
+
     fn add(&self, project: &str, task: String, due_date: Option<DateTime<Local>>) -> Result<bool, Error> {
         let mut client = self.get_client()?;
-        let projects = self.projects()?;
-        let selected_project = projects.iter().find(|p| p.name == project).
-            expect(format!("No project named {}", project).as_str());
+        let project_id = self.project_id(project)?;
 
-        let data = NewTask::new(selected_project.id.clone(), task, due_date.unwrap_or(Local::now()));
+        let data = NewTask::new(project_id, task, due_date.unwrap_or(Local::now()));
         info!("Creating Todoist Task: {:?}", data);
         client.post((), &data)?;
 
         Ok(true)
     }
 
-    fn reschedule(&self, project: &str, task_id: &str, content: String, due_date: Option<DateTime<Local>>) -> Result<bool, Error> {
+    fn reschedule(&self, _project: &str, task_id: &str, content: String, due_date: Option<DateTime<Local>>, recurrence: Option<String>) -> Result<bool, Error> {
         let mut client = self.get_client()?;
-        let projects = self.projects()?;
-        let selected_project = projects.iter().find(|p| p.name == project).
-            expect(format!("No project named {}", project).as_str());
 
-        let data = Task::new(selected_project.id.clone(), Some(task_id.to_string()), content, due_date.unwrap_or(Local::now()));
+        let data = TaskUpdate::new(content, due_date.unwrap_or(Local::now()), recurrence);
         info!("Rescheduling Todoist Task: {:?}", data);
         client.post(task_id, &data)?;
 
@@ -185,5 +257,37 @@ impl TodoistClient for TodoistRestClient {
 
         Ok(true)
     }
+
+    fn comments(&self, task_id: &str) -> Result<Vec<Comment>, Error> {
+        let mut client = self.get_client()?;
+        Ok(client.get_with::<_, Comments>((), &[("task_id", task_id)])?.0)
+    }
+
+    fn add_comment(&self, task_id: &str, content: String) -> Result<bool, Error> {
+        let mut client = self.get_client()?;
+        let data = NewComment { task_id: task_id.to_string(), content };
+        info!("Adding Todoist comment: {:?}", data);
+        client.post((), &data)?;
+
+        Ok(true)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rescheduling_a_recurring_task_keeps_the_recurrence() {
+        let due = Local.ymd(2026, 10, 20).and_hms(23, 59, 59);
+        assert_eq!("every monday starting 2026-10-20", recurrence_starting("every monday", due));
+        assert_eq!("every 3 days starting 2026-10-20", recurrence_starting("every 3 days starting Oct 1", due));
+    }
+
+    #[test]
+    fn updates_only_send_what_changes() {
+        let due = Local.ymd(2026, 10, 20).and_hms(23, 59, 59);
+        let json = serde_json::to_string(&TaskUpdate::new("Water plants".to_string(), due, Some("every day".to_string()))).unwrap();
+        assert_eq!(r#"{"content":"Water plants","due_string":"every day starting 2026-10-20"}"#, json);
+    }
+}
@@ -1,49 +1,109 @@
-use crate::scheduled_item::{ScheduledItem, Scheduler, ScheduleItemType};
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, ItemComment, Capabilities};
+use crate::scheduler_error::SchedulerResult;
 use crate::todoist_client::*;
+use crate::todoist_sync_client::TodoistSyncClient;
 use chrono::{DateTime, Local, TimeZone, Date};
 use regex::Regex;
 use std::error::Error;
 use std::fs::File;
-use restson::Error::HttpError;
 use log::info;
 use std::sync::mpsc::Sender;
 use crate::commands::UICommand;
-use crate::display::{PromptMessage, PromptMessageType};
-use std::time::Duration;
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
 pub struct ApiToken {
     pub token: String
 }
 
+/// Which tasks a Todoist scheduler shows
+#[derive(Debug, Clone)]
+pub enum TodoistSelection {
+    Projects(Vec<String>),
+    AllProjects,
+    Filter(String) // Any Todoist filter query, e.g. "today | overdue" or "@work"
+}
+
 pub struct TodoistScheduler {
     client: Box<dyn TodoistClient>,
-    project: String,
+    selection: TodoistSelection,
+    projects: Vec<Project>,
+    tasks: Vec<Task>,
     cache: Vec<ScheduledItem>,
     ui_tx: Sender<UICommand>
 }
 
-pub(crate) fn create_todoist_scheduler(name: String, project: String, ui_tx: Sender<UICommand>) -> Result<TodoistScheduler, Box<dyn Error>> {
+pub(crate) fn create_todoist_scheduler(name: String, selection: TodoistSelection, sync: bool, ui_tx: Sender<UICommand>) -> Result<TodoistScheduler, Box<dyn Error>> {
     let file = File::open(format!("config/{}.json", name))?;
     let todoist_token: ApiToken = serde_json::from_reader(file).expect("Badly formatted auth token file!");
-    let tdc = TodoistRestClient::new(todoist_token.token);
-    Ok(TodoistScheduler::new(Box::new(tdc), project, ui_tx))
+    let tdc: Box<dyn TodoistClient> = if sync {
+        Box::new(TodoistSyncClient::new(todoist_token.token))
+    } else {
+        Box::new(TodoistRestClient::new(todoist_token.token))
+    };
+    Ok(TodoistScheduler::new(tdc, selection, ui_tx))
 }
 
 impl TodoistScheduler {
-    pub fn new(client: Box<dyn TodoistClient>, project: String, ui_tx: Sender<UICommand>) -> Self {
-        TodoistScheduler { client, project, ui_tx, cache: Vec::new() }
+    pub fn new(client: Box<dyn TodoistClient>, selection: TodoistSelection, ui_tx: Sender<UICommand>) -> Self {
+        TodoistScheduler { client, selection, ui_tx, projects: Vec::new(), tasks: Vec::new(), cache: Vec::new() }
+    }
+
+    fn load_tasks(&self) -> Result<Vec<Task>, restson::Error> {
+        match &self.selection {
+            TodoistSelection::Projects(projects) => {
+                let mut tasks = vec![];
+                for project in projects {
+                    tasks.extend(self.client.tasks(project)?);
+                }
+                Ok(tasks)
+            }
+            TodoistSelection::AllProjects => self.client.all_tasks(),
+            TodoistSelection::Filter(query) => self.client.filter(query)
+        }
+    }
+
+    fn project_name(&self, project_id: &String) -> Option<String> {
+        self.projects.iter().find(|p| p.id == *project_id).map(|p| p.name.clone())
+    }
+
+    /// New tasks go to the first configured project, or the Inbox when we're showing
+    /// everything, unless the description names another one Todoist-style: "Buy paint #Home"
+    fn target_project(&self, description: &String) -> (String, String) {
+        for word in description.split_whitespace().filter(|w| w.starts_with('#')) {
+            if let Some(p) = self.projects.iter().find(|p| p.name.eq_ignore_ascii_case(&word[1..])) {
+                let remaining = description.split_whitespace().filter(|w| w != &word).collect::<Vec<_>>().join(" ");
+                return (p.name.clone(), remaining);
+            }
+        }
+
+        let default = match &self.selection {
+            TodoistSelection::Projects(projects) => projects.first().cloned().unwrap_or("Inbox".to_string()),
+            _ => "Inbox".to_string()
+        };
+        (default, description.clone())
     }
 }
 
 impl Scheduler for TodoistScheduler{
     fn id(&self) -> String {
-        format!("todoist:{}", self.project)
+        match &self.selection {
+            TodoistSelection::Projects(projects) => format!("todoist:{}", projects.join(",")),
+            TodoistSelection::AllProjects => "todoist:*".to_string(),
+            TodoistSelection::Filter(query) => format!("todoist:{}", query)
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: true, delete: false, times: true, comments: true }
     }
 
-    fn refresh(&mut self) -> Result<(), Box<dyn Error>> {
-        self.cache = self.client.tasks(&self.project)?.iter().
-            map(|t| task_to_scheduled_item(&self.project, t)).collect();
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        self.projects = self.client.projects()?;
+        self.tasks = self.load_tasks()?;
+
+        let scheduler_id = self.id();
+        self.cache = self.tasks.iter().
+            map(|t| task_to_scheduled_item(&scheduler_id, self.project_name(&t.project_id), t)).collect();
 
         Ok(())
     }
@@ -52,79 +112,74 @@ impl Scheduler for TodoistScheduler{
         self.cache.clone()
     }
 
-    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> anyhow::Result<bool> {
-        info!("Adding to Todoist project '{}'", self.project);
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let (project, description) = self.target_project(description);
+        info!("Adding to Todoist project '{}'", project);
 
-        let handled = match self.client.add(self.project.as_str(), description.clone(), due_date) {
-            Ok(result) => result,
-            Err(e) => {
-                self.ui_tx.send(UICommand::Toast(PromptMessage::new(e.to_string(), Duration::from_secs(10), PromptMessageType::Error)));
-                anyhow::bail!(e.to_string())
-            }
-        };
+        let handled = self.client.add(project.as_str(), description, due_date)?;
 
         self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()));
 
         Ok(handled)
     }
 
-    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> anyhow::Result<bool> {
-        let handled = match self.client.reschedule(self.project.as_str(), id.as_str(), description.clone(), due_date) {
-            Ok(result) => result,
-            Err(e) => {
-                self.ui_tx.send(UICommand::Toast(PromptMessage::new(e.to_string(), Duration::from_secs(10), PromptMessageType::Error)));
-                anyhow::bail!(e.to_string())
-            }
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let task = self.tasks.iter().find(|t| t.id == *id);
+        let project = match task.and_then(|t| self.project_name(&t.project_id)) {
+            Some(project) => project,
+            None => self.target_project(description).0
         };
+        let recurrence = task.and_then(|t| t.due.as_ref()).filter(|d| d.is_recurring).map(|d| d.string.clone());
+
+        let handled = self.client.reschedule(project.as_str(), id.as_str(), description.clone(), due_date, recurrence)?;
 
         self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()));
 
         Ok(handled)
     }
 
-    fn remove(&mut self, target: &String) -> anyhow::Result<bool> {
-        info!("Looking for a task '{}' in project {}", target, self.project);
-        let tasks = self.client.tasks(self.project.as_str());
-        let res = match tasks {
-            Ok(tasks) => {
-                let task = tasks.iter().find(|t| t.clone().content == *target);
-                match task{
-                    Some(t) => {
-                        info!("Found '{}'! Attempting to close it!", t.content);
-                        match self.client.close(&t.id) {
-                            Ok(result) => {
-                                info!("Closed {}: {}", t.content, result);
-                                self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()));
-                                result
-                            },
-                            Err(e) => {
-                                match e {
-                                    HttpError(_code, msg) => { anyhow::bail!(msg); },
-                                    _ => anyhow::bail!(e.to_string())
-                                }
-                            }
-                        }
-                    },
-                    None => {
-                        info!("Could not find task {:?} in {:?}", task, tasks);
-                        false
-                    }
-                }
-            },
-            Err(e) => anyhow::bail!(e.to_string())
-        };
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        // Todoist moves recurring tasks on to their next date instead of closing them,
+        // so the refresh afterwards shows the next occurrence.
+        let recurring = self.tasks.iter().find(|t| t.id == *id).and_then(|t| t.due.as_ref()).map_or(false, |d| d.is_recurring);
+        info!("Attempting to close task {} in {} (recurring: {})", id, self.id(), recurring);
+        let res = self.client.close(id)?;
+        info!("Closed {}: {}", id, res);
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()));
 
         Ok(res)
     }
+
+    fn comments(&mut self, id: &String) -> SchedulerResult<Vec<ItemComment>> {
+        let comments = self.client.comments(id.as_str())?;
+
+        Ok(comments.iter().map(|c| ItemComment {
+            posted_at: DateTime::parse_from_rfc3339(&c.posted_at).ok().map(|t| t.with_timezone(&Local)),
+            content: c.content.clone()
+        }).collect())
+    }
+
+    fn add_comment(&mut self, id: &String, content: &String) -> SchedulerResult<bool> {
+        Ok(self.client.add_comment(id.as_str(), content.clone())?)
+    }
 }
 
-fn td_time_to_datetime(due: &Option<TodoistDate>) -> DateTime<Local> {
+/// The task's due time, and whether it's only due on a date rather than at a time.
+/// Tasks with no due date get the end of today, which means nothing once they're marked undated.
+fn td_time_to_datetime(due: &Option<TodoistDate>) -> (DateTime<Local>, bool) {
+    let exact = due.as_ref().
+        and_then(|td| td.datetime.as_ref()).
+        and_then(|dt| DateTime::parse_from_rfc3339(dt).ok());
+    if let Some(dt) = exact {
+        return (dt.with_timezone(&Local), false);
+    }
+
     let date = match due {
         None => Local::today(),
         Some(_td) => extract_date(_td)
     };
 
-    end_of_day(date)
+    (end_of_day(date), true)
 }
 
 fn extract_date(td: &TodoistDate) -> Date<Local> {
@@ -139,16 +194,33 @@ fn extract_date(td: &TodoistDate) -> Date<Local> {
     }
 }
 
-fn task_to_scheduled_item(account_id: &String, t: &Task) -> ScheduledItem {
-    let id = format!("todoist:{}", t.id);
-    ScheduledItem::new(
-        id,
-        format!("todoist:{}", account_id),
+fn task_to_scheduled_item(scheduler_id: &String, project: Option<String>, t: &Task) -> ScheduledItem {
+    let (due, all_day) = td_time_to_datetime(&t.due);
+    let mut item = ScheduledItem::new(
+        ItemId::new(scheduler_id, &t.id),
         ScheduleItemType::Todo,
         t.content.clone(),
-        td_time_to_datetime(&t.due),
+        due,
         None,
-        None)
+        None);
+    item.all_day = all_day;
+    item.undated = t.due.is_none();
+    item.priority = td_priority(t.priority);
+    item.tags = project.iter().map(|p| format!("#{}", p)).
+        chain(t.labels.iter().map(|l| format!("@{}", l))).
+        collect();
+    item.notes = if t.description.is_empty() { None } else { Some(t.description.clone()) };
+    item.link = if t.url.is_empty() { None } else { Some(t.url.clone()) };
+    item.parent_id = t.parent_id.as_ref().map(|p| ItemId::new(scheduler_id, p));
+    item
+}
+
+fn td_priority(priority: u64) -> Option<u8> {
+    // The API counts backwards from the app: 4 is "p1" (urgent) and 1 is "p4" (normal)
+    match priority {
+        2..=4 => Some((5 - priority) as u8),
+        _ => None
+    }
 }
 
 fn end_of_day(date: Date<Local>) -> DateTime<Local> {
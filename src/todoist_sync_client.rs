@@ -0,0 +1,321 @@
+use restson::{RestPath, Error, RestClient};
+use chrono::{DateTime, Local, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use log::info;
+use crate::scheduled_item::is_all_day;
+use crate::todoist_client::{TodoistClient, TodoistRestClient, Project, Task, Comment, TodoistDate, URL_BASE, recurrence_starting};
+
+// Incremental client for the Todoist Sync API (https://developer.todoist.com/sync/v9/).
+// The first call downloads everything, after that we only ask for what changed since
+// the last sync_token and patch our local copy.
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+struct SyncProject {
+    id: String,
+    name: String,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+struct SyncDue {
+    date: String, // YYYY-MM-DD, or a full datetime if the task has a time
+    string: Option<String>,
+    timezone: Option<String>,
+    #[serde(default)]
+    is_recurring: bool,
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+struct SyncItem {
+    id: String,
+    project_id: String,
+    content: String,
+    #[serde(default)]
+    checked: bool,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    priority: u64,
+    child_order: Option<usize>,
+    due: Option<SyncDue>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    description: String,
+    parent_id: Option<String>,
+}
+
+#[derive(Serialize,Debug)]
+struct SyncRequest {
+    sync_token: String,
+    resource_types: Vec<String>,
+}
+
+#[derive(Serialize,Debug)]
+struct CommandRequest {
+    commands: Vec<Command>,
+}
+
+#[derive(Serialize,Debug)]
+struct Command {
+    #[serde(rename = "type")]
+    command_type: String,
+    uuid: String,
+    temp_id: Option<String>,
+    args: serde_json::Value,
+}
+
+#[derive(Deserialize,Debug)]
+struct SyncResponse {
+    sync_token: String,
+    #[serde(default)]
+    full_sync: bool,
+    #[serde(default)]
+    projects: Vec<SyncProject>,
+    #[serde(default)]
+    items: Vec<SyncItem>,
+}
+
+#[derive(Deserialize,Debug)]
+struct CommandResponse {
+    sync_status: HashMap<String, serde_json::Value>,
+}
+
+impl RestPath<()> for SyncRequest {
+    fn get_path(_: ()) -> Result<String,Error> { Ok("sync/v9/sync".to_string()) }
+}
+
+impl RestPath<()> for CommandRequest {
+    fn get_path(_: ()) -> Result<String,Error> { Ok("sync/v9/sync".to_string()) }
+}
+
+#[derive(Default)]
+struct SyncState {
+    sync_token: Option<String>,
+    projects: HashMap<String, SyncProject>,
+    items: HashMap<String, SyncItem>,
+}
+
+pub struct TodoistSyncClient {
+    token: String,
+    state: Mutex<SyncState>,
+    rest: TodoistRestClient, // Filter queries and comments still go through REST
+}
+
+static COMMAND_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+impl TodoistSyncClient {
+    pub fn new(token: String) -> TodoistSyncClient {
+        TodoistSyncClient { rest: TodoistRestClient::new(token.clone()), token, state: Mutex::new(SyncState::default()) }
+    }
+
+    fn get_client(&self) -> Result<RestClient, Error> {
+        let mut client = RestClient::new(URL_BASE)?;
+        client.set_header("Authorization", format!("Bearer {}", self.token).as_str())?;
+        Ok(client)
+    }
+
+    /// Pull everything that changed since the last sync into our local state
+    fn sync(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        let request = SyncRequest {
+            sync_token: state.sync_token.clone().unwrap_or("*".to_string()),
+            resource_types: vec!["projects".to_string(), "items".to_string()],
+        };
+
+        let response: SyncResponse = self.get_client()?.post_capture((), &request)?;
+        info!("Todoist sync: full={} projects={} items={}", response.full_sync, response.projects.len(), response.items.len());
+
+        if response.full_sync {
+            state.projects.clear();
+            state.items.clear();
+        }
+
+        for project in response.projects {
+            if project.is_deleted { state.projects.remove(&project.id); }
+            else { state.projects.insert(project.id.clone(), project); }
+        }
+
+        for item in response.items {
+            if item.is_deleted || item.checked { state.items.remove(&item.id); }
+            else { state.items.insert(item.id.clone(), item); }
+        }
+
+        state.sync_token = Some(response.sync_token);
+        Ok(())
+    }
+
+    fn project_id(&self, project: &str) -> Result<String, Error> {
+        let known = self.state.lock().unwrap().projects.values().find(|p| p.name == project).map(|p| p.id.clone());
+        match known {
+            Some(id) => Ok(id),
+            None => {
+                // Might be brand new - check with the server before giving up
+                self.sync()?;
+                let state = self.state.lock().unwrap();
+                state.projects.values().find(|p| p.name == project).map(|p| p.id.clone()).
+                    ok_or(Error::HttpError(404, format!("No project named {}", project)))
+            }
+        }
+    }
+
+    fn send_command(&self, command_type: &str, args: serde_json::Value) -> Result<bool, Error> {
+        let uuid = command_uuid();
+        let command = Command {
+            command_type: command_type.to_string(),
+            uuid: uuid.clone(),
+            temp_id: if command_type == "item_add" { Some(command_uuid()) } else { None },
+            args,
+        };
+        info!("Sending Todoist command: {:?}", command);
+
+        let response: CommandResponse = self.get_client()?.post_capture((), &CommandRequest { commands: vec![command] })?;
+        match response.sync_status.get(&uuid) {
+            Some(serde_json::Value::String(status)) if status == "ok" => Ok(true),
+            Some(error) => Err(Error::HttpError(400, error.to_string())),
+            None => Ok(false)
+        }
+    }
+}
+
+impl TodoistClient for TodoistSyncClient {
+    fn projects(&self) -> Result<Vec<Project>, Error> {
+        self.sync()?;
+        let state = self.state.lock().unwrap();
+        Ok(state.projects.values().map(|p| Project::new(p.id.clone(), p.name.clone())).collect())
+    }
+
+    fn tasks(&self, project: &str) -> Result<Vec<Task>, Error> {
+        self.sync()?;
+        let project_id = self.project_id(project)?;
+        let state = self.state.lock().unwrap();
+        Ok(state.items.values().
+            filter(|i| i.project_id == project_id).
+            map(item_to_task).
+            collect())
+    }
+
+    fn all_tasks(&self) -> Result<Vec<Task>, Error> {
+        self.sync()?;
+        let state = self.state.lock().unwrap();
+        Ok(state.items.values().map(item_to_task).collect())
+    }
+
+    fn filter(&self, query: &str) -> Result<Vec<Task>, Error> {
+        self.rest.filter(query)
+    }
+
+    fn add(&self, project: &str, task: String, due_date: Option<DateTime<Local>>) -> Result<bool, Error> {
+        let project_id = self.project_id(project)?;
+        self.send_command("item_add", serde_json::json!({
+            "content": task,
+            "project_id": project_id,
+            "due": { "date": sync_date(due_date.unwrap_or(Local::now())) },
+        }))
+    }
+
+    fn reschedule(&self, _project: &str, task_id: &str, content: String, due_date: Option<DateTime<Local>>, recurrence: Option<String>) -> Result<bool, Error> {
+        let due_date = due_date.unwrap_or(Local::now());
+        let due = match recurrence {
+            // Setting a plain date would turn a recurring task into a one-off
+            Some(recurrence) => serde_json::json!({ "string": recurrence_starting(&recurrence, due_date) }),
+            None => serde_json::json!({ "date": sync_date(due_date) })
+        };
+
+        self.send_command("item_update", serde_json::json!({
+            "id": task_id,
+            "content": content,
+            "due": due,
+        }))
+    }
+
+    fn close(&self, task_id: &str) -> Result<bool, Error> {
+        self.send_command("item_close", serde_json::json!({ "id": task_id }))
+    }
+
+    fn comments(&self, task_id: &str) -> Result<Vec<Comment>, Error> {
+        self.rest.comments(task_id)
+    }
+
+    fn add_comment(&self, task_id: &str, content: String) -> Result<bool, Error> {
+        self.rest.add_comment(task_id, content)
+    }
+}
+
+fn item_to_task(item: &SyncItem) -> Task {
+    let due = item.due.as_ref().map(|d| TodoistDate {
+        string: d.string.clone().unwrap_or_default(),
+        date: d.date.chars().take(10).collect(),
+        datetime: sync_datetime(&d.date),
+        timezone: d.timezone.clone(),
+        is_recurring: d.is_recurring,
+    });
+
+    Task {
+        id: item.id.clone(),
+        project_id: item.project_id.clone(),
+        content: item.content.clone(),
+        is_completed: item.checked,
+        order: item.child_order,
+        priority: item.priority,
+        due_string: None,
+        due_date: None,
+        due_datetime: None,
+        due,
+        url: format!("https://todoist.com/showTask?id={}", item.id),
+        labels: item.labels.clone(),
+        description: item.description.clone(),
+        parent_id: item.parent_id.clone(),
+    }
+}
+
+/// Timed dues come back "floating" (YYYY-MM-DDTHH:MM:SS in the user's own time) or fixed to a
+/// moment with a trailing Z - either way hand them on as RFC 3339, like the REST API does
+fn sync_datetime(date: &str) -> Option<String> {
+    if date.len() <= 10 {
+        return None;
+    }
+    if DateTime::parse_from_rfc3339(date).is_ok() {
+        return Some(date.to_string());
+    }
+
+    NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S").ok().
+        and_then(|naive| Local.from_local_datetime(&naive).earliest()).
+        map(|due| due.to_rfc3339())
+}
+
+fn sync_date(due: DateTime<Local>) -> String {
+    if is_all_day(due) {
+        // A plain date is due that day, with no time attached
+        return due.format("%Y-%m-%d").to_string();
+    }
+    // Dates ending in Z are "fixed" to that moment, regardless of the user's timezone
+    due.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn command_uuid() -> String {
+    // Todoist just needs these to be unique per command, so nanos + a counter will do
+    let nanos = Local::now().timestamp_nanos() as u64;
+    let count = COMMAND_COUNT.fetch_add(1, Ordering::SeqCst) as u64;
+    format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            (nanos >> 32) as u32, (nanos >> 16) as u16, nanos as u16, count as u16, std::process::id() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floating_and_date_only_dues() {
+        let floating = sync_datetime("2026-10-18T09:30:00").unwrap();
+        assert_eq!(Local.ymd(2026, 10, 18).and_hms(9, 30, 0), DateTime::parse_from_rfc3339(&floating).unwrap());
+        assert_eq!(Some("2026-10-18T09:30:00Z".to_string()), sync_datetime("2026-10-18T09:30:00Z"));
+        assert_eq!(None, sync_datetime("2026-10-18"));
+
+        assert_eq!("2026-10-18", sync_date(Local.ymd(2026, 10, 18).and_hms(23, 59, 59)));
+        assert!(sync_date(Local.ymd(2026, 10, 18).and_hms(9, 30, 0)).ends_with('Z'));
+    }
+}
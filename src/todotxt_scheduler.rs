@@ -0,0 +1,320 @@
+use crate::scheduled_item::{ScheduledItem, ItemId, Scheduler, ScheduleItemType, Capabilities, end_of_day};
+use crate::scheduler_error::{SchedulerError, SchedulerResult};
+use crate::commands::UICommand;
+use chrono::{DateTime, Local, NaiveDate};
+use regex::Regex;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use crate::text_lines::{line_ids, find_line, write_lines};
+use log::info;
+
+/// One line of a todo.txt file. See https://github.com/todotxt/todo.txt for the format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoTxtTask {
+    pub completed: bool,
+    pub priority: Option<char>,
+    pub description: String,
+    pub due: Option<NaiveDate>,
+    pub tags: Vec<String>,
+}
+
+pub struct TodoTxtScheduler {
+    name: String,
+    path: String,
+    cache: Vec<ScheduledItem>,
+    ui_tx: Sender<UICommand>
+}
+
+pub(crate) fn create_todotxt_scheduler(name: String, path: String, ui_tx: Sender<UICommand>) -> Result<TodoTxtScheduler, Box<dyn Error>> {
+    Ok(TodoTxtScheduler::new(name, path, ui_tx))
+}
+
+impl TodoTxtScheduler {
+    pub fn new(name: String, path: String, ui_tx: Sender<UICommand>) -> Self {
+        TodoTxtScheduler { name, path, ui_tx, cache: Vec::new() }
+    }
+
+    fn read_lines(&self) -> anyhow::Result<Vec<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(contents.lines().map(|l| l.to_string()).collect()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    /// Find the current position of a line we saw on the last refresh. Other editors
+    /// may have shuffled the file around since then.
+    fn locate(&self, lines: &Vec<String>, id: &String) -> Option<usize> {
+        find_line(id, Path::new(&self.path), lines)
+    }
+}
+
+impl Scheduler for TodoTxtScheduler {
+    fn id(&self) -> String {
+        format!("todotxt:{}", self.name)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { add: true, reschedule: true, complete: true, delete: false, times: false, comments: false }
+    }
+
+    fn refresh(&mut self) -> SchedulerResult<()> {
+        // Always re-read the file, it's fair game for any other todo.txt tool
+        let lines = self.read_lines()?;
+
+        let scheduler_id = self.id();
+        let ids = line_ids(Path::new(&self.path), &lines);
+        self.cache = lines.iter().zip(ids.iter()).
+            filter_map(|(line, id)| parse_line(line).map(|t| (id, t))).
+            filter(|(_, t)| !t.completed).
+            map(|(id, t)| task_to_scheduled_item(&scheduler_id, id, &t)).
+            collect();
+
+        Ok(())
+    }
+
+    fn schedule(&self) -> Vec<ScheduledItem> {
+        self.cache.clone()
+    }
+
+    fn add(&mut self, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        info!("Adding '{}' to {}", description, self.path);
+        let mut lines = self.read_lines()?;
+        let mut line = format!("{} {}", Local::today().format("%Y-%m-%d"), description);
+        if let Some(due) = due_date {
+            line.push_str(&format!(" due:{}", due.format("%Y-%m-%d")));
+        }
+        lines.push(line);
+        write_lines(Path::new(&self.path), &lines)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn update(&mut self, id: &String, description: &String, due_date: Option<DateTime<Local>>) -> SchedulerResult<bool> {
+        let mut lines = self.read_lines()?;
+        let idx = match self.locate(&lines, id) {
+            None => return Err(SchedulerError::NotFound(format!("Task {} is no longer in {}", id, self.path))),
+            Some(idx) => idx
+        };
+
+        let mut line = lines[idx].clone();
+        if let Some(task) = parse_line(&line) {
+            if task.description != *description {
+                line = set_description(&line, description);
+            }
+        }
+        if let Some(due) = due_date {
+            line = set_due(&line, due.date().naive_local());
+        }
+        lines[idx] = line;
+        write_lines(Path::new(&self.path), &lines)?;
+
+        self.ui_tx.send(UICommand::SubmitCommand("refresh".to_string()))?;
+
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &String) -> SchedulerResult<bool> {
+        info!("Looking for task {} in {}", id, self.path);
+        let mut lines = self.read_lines()?;
+        match self.locate(&lines, id) {
+            None => Err(SchedulerError::NotFound(format!("Task {} is no longer in {}", id, self.path))),
+            Some(idx) => {
+                lines[idx] = complete_line(&lines[idx], Local::today().naive_local());
+                write_lines(Path::new(&self.path), &lines)?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+fn task_to_scheduled_item(scheduler_id: &String, id: &String, task: &TodoTxtTask) -> ScheduledItem {
+    let due = match task.due {
+        Some(date) => end_of_day(date),
+        None => Local::today().and_hms(23, 59, 59)
+    };
+
+    let mut item = ScheduledItem::new(
+        ItemId::new(scheduler_id, id),
+        ScheduleItemType::Todo,
+        task.description.clone(),
+        due,
+        None,
+        None);
+    item.all_day = true;
+    item.undated = task.due.is_none();
+    // (A) is the most urgent priority, (Z) the least
+    item.priority = task.priority.and_then(|p| (p as u8).checked_sub(b'A')).map(|p| p + 1);
+    item.tags = task.tags.clone();
+    item
+}
+
+lazy_static! {
+    static ref COMPLETED_REGEX: Regex = Regex::new(r"^x\s+(\d{4}-\d{2}-\d{2}\s+){0,2}").unwrap();
+    static ref PRIORITY_REGEX: Regex = Regex::new(r"^\(([A-Z])\)\s+").unwrap();
+    static ref CREATED_REGEX: Regex = Regex::new(r"^\d{4}-\d{2}-\d{2}\s+").unwrap();
+}
+
+pub fn parse_line(line: &str) -> Option<TodoTxtTask> {
+    let line = line.trim();
+    if line.is_empty() { return None; }
+
+    let mut rest = line;
+    let completed = COMPLETED_REGEX.is_match(rest);
+    if completed {
+        rest = &rest[COMPLETED_REGEX.find(rest).unwrap().end()..];
+    }
+
+    let mut priority = None;
+    if let Some(cap) = PRIORITY_REGEX.captures(rest) {
+        priority = cap[1].chars().next();
+        rest = &rest[cap.get(0).unwrap().end()..];
+    }
+
+    if let Some(m) = CREATED_REGEX.find(rest) {
+        rest = &rest[m.end()..];
+    }
+
+    let mut due = None;
+    let mut words = vec![];
+    let mut tags = vec![];
+    for word in rest.split_whitespace() {
+        if word.starts_with('+') || word.starts_with('@') {
+            tags.push(word.to_string());
+        }
+
+        match key_value(word) {
+            Some(("due", value)) => due = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+            // Same A-Z as the (A) marker, anything else isn't a priority
+            Some(("pri", value)) if priority.is_none() => priority = Some(value).
+                filter(|v| v.len() == 1).
+                and_then(|v| v.chars().next()).
+                filter(|p| p.is_ascii_uppercase()),
+            Some(_) => {} // other extensions are left alone
+            None => words.push(word)
+        }
+    }
+
+    Some(TodoTxtTask { completed, priority, description: words.join(" "), due, tags })
+}
+
+fn key_value(word: &str) -> Option<(&str, &str)> {
+    // "key:value", but not urls like "http://..."
+    let mut parts = word.splitn(2, ':');
+    let (key, value) = (parts.next()?, parts.next()?);
+    if key.is_empty() || value.is_empty() || value.starts_with("//") { return None; }
+    Some((key, value))
+}
+
+/// Put a new description on the line, keeping its completion and priority markers, creation date
+/// and key:value extensions
+fn set_description(line: &str, description: &str) -> String {
+    let line = line.trim();
+    let mut rest = line;
+    for regex in [&*COMPLETED_REGEX, &*PRIORITY_REGEX, &*CREATED_REGEX] {
+        if let Some(m) = regex.find(rest) {
+            rest = &rest[m.end()..];
+        }
+    }
+
+    let prefix = &line[..line.len() - rest.len()];
+    let extensions: Vec<&str> = rest.split_whitespace().filter(|w| key_value(w).is_some()).collect();
+    let mut words = vec![description.trim()];
+    words.extend(extensions);
+    format!("{}{}", prefix, words.join(" "))
+}
+
+fn set_due(line: &str, due: NaiveDate) -> String {
+    let due_tag = format!("due:{}", due.format("%Y-%m-%d"));
+    let mut found = false;
+    let mut words: Vec<String> = line.split(' ').
+        map(|w| if w.starts_with("due:") { found = true; due_tag.clone() } else { w.to_string() }).
+        collect();
+    if !found { words.push(due_tag); }
+    words.join(" ")
+}
+
+fn complete_line(line: &str, today: NaiveDate) -> String {
+    lazy_static! {
+        static ref PRIORITY_REGEX: Regex = Regex::new(r"^\(([A-Z])\)\s+(.*)$").unwrap();
+    }
+
+    // Completed tasks lose their priority marker, but keep it around as pri:X
+    let line = match PRIORITY_REGEX.captures(line) {
+        Some(cap) => format!("{} pri:{}", &cap[2], &cap[1]),
+        None => line.to_string()
+    };
+
+    format!("x {} {}", today.format("%Y-%m-%d"), line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_priority_dates_and_tags() {
+        let task = parse_line("(A) 2026-10-01 Call mom +family @phone due:2026-10-20").unwrap();
+        assert_eq!(TodoTxtTask {
+            completed: false,
+            priority: Some('A'),
+            description: "Call mom +family @phone".to_string(),
+            due: Some(NaiveDate::from_ymd(2026, 10, 20)),
+            tags: vec!["+family".to_string(), "@phone".to_string()],
+        }, task);
+    }
+
+    #[test]
+    fn tasks_without_a_due_date_are_undated() {
+        let scheduler_id = "todotxt:todo".to_string();
+        let undated = task_to_scheduled_item(&scheduler_id, &"1".to_string(), &parse_line("Call mom").unwrap());
+        assert!(undated.undated);
+        let due = task_to_scheduled_item(&scheduler_id, &"2".to_string(), &parse_line("Call mom due:2026-10-20").unwrap());
+        assert!(!due.undated);
+    }
+
+    #[test]
+    fn parses_completed_tasks() {
+        let task = parse_line("x 2026-10-18 2026-10-01 Call mom pri:B").unwrap();
+        assert!(task.completed);
+        assert_eq!(Some('B'), task.priority);
+        assert_eq!("Call mom", task.description);
+    }
+
+    #[test]
+    fn ignores_priorities_outside_a_to_z() {
+        assert_eq!(None, parse_line("x 2026-10-18 Call mom pri:3").unwrap().priority);
+        assert_eq!(None, parse_line("x 2026-10-18 Call mom pri:AB").unwrap().priority);
+        assert_eq!(None, parse_line("x 2026-10-18 Call mom pri:!").unwrap().priority);
+    }
+
+    #[test]
+    fn urls_are_not_mistaken_for_keys() {
+        let task = parse_line("Read https://example.com/article").unwrap();
+        assert_eq!("Read https://example.com/article", task.description);
+    }
+
+    #[test]
+    fn completing_moves_the_priority_to_a_key() {
+        let done = complete_line("(A) 2026-10-01 Call mom due:2026-10-20", NaiveDate::from_ymd(2026, 10, 18));
+        assert_eq!("x 2026-10-18 2026-10-01 Call mom due:2026-10-20 pri:A", done);
+    }
+
+    #[test]
+    fn changing_the_description_keeps_everything_else() {
+        assert_eq!("(A) 2026-10-01 Call dad +family due:2026-10-20 rec:1w",
+                   set_description("(A) 2026-10-01 Call mom  +family due:2026-10-20 rec:1w", "Call dad +family"));
+        assert_eq!("x 2026-10-18 2026-10-01 Call dad", set_description("x 2026-10-18 2026-10-01 Call mom", "Call dad"));
+    }
+
+    #[test]
+    fn setting_the_due_date_replaces_an_existing_one() {
+        assert_eq!("Call mom due:2026-10-22", set_due("Call mom due:2026-10-20", NaiveDate::from_ymd(2026, 10, 22)));
+        assert_eq!("Call mom due:2026-10-22", set_due("Call mom", NaiveDate::from_ymd(2026, 10, 22)));
+    }
+}